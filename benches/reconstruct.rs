@@ -0,0 +1,56 @@
+//! time `output_phase` opening 1000 output wires to every party, with all parties running
+//! concurrently over in-process channels: the exchange of output shares and the batched
+//! reconstruction together
+//!
+//! run with `cargo bench --bench reconstruct`
+
+use ark_bn254::Fr;
+use std::time::{Duration, Instant};
+
+use bgw::circuit::Circuit;
+use bgw::party::{Party, PartyConfig};
+use bgw::sharing::shamir_share_batch;
+use bgw::transport::ChannelTransport;
+
+const OUTPUTS: usize = 1000;
+const RUNS: u32 = 5;
+
+/// one `output_phase` across `n` parties, returning the slowest party's time
+async fn open_all(n: usize, t: usize, secrets: &[Fr]) -> Duration {
+    let dealt = shamir_share_batch(secrets, t, n);
+    let wires: Vec<usize> = (0..secrets.len()).collect();
+
+    let handles: Vec<_> = ChannelTransport::network(n).into_iter().enumerate().map(|(pid, transport)| {
+        let mut party = Party::with_config(pid, PartyConfig::new(n, t), transport);
+        for (wire_id, shares) in dealt.iter().enumerate() {
+            party.set_share(wire_id, shares[pid]);
+        }
+        let wires = wires.clone();
+        tokio::spawn(async move {
+            // none of the wires are gates of this (empty) circuit, so each is opened to everyone
+            let start = Instant::now();
+            let output = party.output_phase(&Circuit::new(), &wires).await.unwrap();
+            (output, start.elapsed()) // the party hangs up once done, as a real one would
+        })
+    }).collect();
+
+    let mut slowest = Duration::ZERO;
+    for h in handles {
+        let (output, elapsed) = h.await.unwrap();
+        assert_eq!(output[&(OUTPUTS - 1)], secrets[OUTPUTS - 1]);
+        slowest = slowest.max(elapsed);
+    }
+    slowest
+}
+
+#[tokio::main]
+async fn main() {
+    let secrets: Vec<Fr> = (0..OUTPUTS as u64).map(Fr::from).collect();
+    for (n, t) in [(7, 3), (31, 15)] {
+        let mut total = Duration::ZERO;
+        for _ in 0..RUNS {
+            total += open_all(n, t, &secrets).await;
+        }
+        println!("n = {:2}, {} outputs: output_phase {:?}", n, OUTPUTS, total / RUNS);
+    }
+}
@@ -1,40 +1,117 @@
-use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use std::collections::HashMap;
+use std::io::BufRead;
 
 /// supported gate types in the arithmetic circuit
 #[derive(Clone, Debug)]
-pub enum GateType {
+pub enum GateType<F> {
     Input,
     Add,
     Mul,
-    ConstMul(Fr),
+    ConstMul(F),
+    AddConst(F), // adds a public constant
+    ToAdditive, // Shamir share → additive share of the same secret (local)
+    ToShamir,   // additive share → Shamir share of the same secret (one round)
     Output,
 }
 
 /// a gate in the arithmetic circuit
 #[derive(Clone, Debug)]
-pub struct Gate {
+pub struct Gate<F> {
     pub id: usize,
-    pub gate_type: GateType,
+    pub gate_type: GateType<F>,
     pub left: Option<usize>,
     pub right: Option<usize>,
     pub owner: Option<usize>, // only applies to input wires (specifies party that owns the wire)
+    pub default: Option<F>, // only applies to input wires (value used if the owner never shares it)
 }
 
-#[derive(Clone)]
-pub struct Circuit {
-    pub gates: Vec<Gate>,
+/// structural problems found while walking a circuit
+#[derive(Clone, Debug, PartialEq)]
+pub enum CircuitError {
+    Cycle { gate_id: usize },         // a back-edge was found at this gate
+    DanglingWire { gate_id: usize, wire: usize }, // `wire` is referenced by `gate_id` but out of bounds
+    MissingOperand { gate_id: usize }, // the gate type needs an operand that isn't wired
+    MissingOwner { gate_id: usize },   // an Input gate that no party owns
+    OutputAsOperand { gate_id: usize, output: usize }, // `gate_id` reads from the Output gate `output`
 }
 
-impl Circuit {
+impl std::fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CircuitError::Cycle { gate_id } => write!(f, "cycle detected at gate {}", gate_id),
+            CircuitError::DanglingWire { gate_id, wire } => {
+                write!(f, "gate {} references undefined wire {}", gate_id, wire)
+            }
+            CircuitError::MissingOperand { gate_id } => write!(f, "gate {} is missing an operand", gate_id),
+            CircuitError::MissingOwner { gate_id } => write!(f, "input gate {} has no owner", gate_id),
+            CircuitError::OutputAsOperand { gate_id, output } => {
+                write!(f, "gate {} uses output gate {} as an operand", gate_id, output)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CircuitError {}
+
+/// errors from parsing a circuit description (line numbers are 1-based)
+#[derive(Debug)]
+pub enum ParseError {
+    Io(std::io::Error),
+    UnexpectedEof, // the header is incomplete
+    InvalidHeader { line: usize },
+    InvalidGate { line: usize },
+    UnsupportedGate { line: usize, op: String },
+    UndefinedWire { line: usize, wire: usize }, // used before any gate or input defines it
+    OwnerCount { expected: usize, got: usize }, // one owner is needed per input value
+    GateCount { expected: usize, got: usize },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "read failed: {}", e),
+            ParseError::UnexpectedEof => write!(f, "unexpected end of input in header"),
+            ParseError::InvalidHeader { line } => write!(f, "line {}: invalid header", line),
+            ParseError::InvalidGate { line } => write!(f, "line {}: invalid gate", line),
+            ParseError::UnsupportedGate { line, op } => write!(f, "line {}: unsupported gate {}", line, op),
+            ParseError::UndefinedWire { line, wire } => write!(f, "line {}: wire {} is used before it is defined", line, wire),
+            ParseError::OwnerCount { expected, got } => write!(f, "expected {} input owners, got {}", expected, got),
+            ParseError::GateCount { expected, got } => write!(f, "header declares {} gates, found {}", expected, got),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<std::io::Error> for ParseError {
+    fn from(e: std::io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Circuit<F> {
+    pub gates: Vec<Gate<F>>,
+}
+
+impl<F: PrimeField> Circuit<F> {
     pub fn new() -> Self {
         Circuit {
             gates: Vec::new(),
         }
     }
 
-    pub fn add_gate(&mut self, gate_type: GateType, left: Option<usize>, right: Option<usize>, owner: Option<usize>) -> usize {
+    pub fn add_gate(&mut self, gate_type: GateType<F>, left: Option<usize>, right: Option<usize>, owner: Option<usize>) -> usize {
         let id = self.gates.len();
-        self.gates.push(Gate {id, gate_type, left, right, owner});
+        self.gates.push(Gate {id, gate_type, left, right, owner, default: None});
+        id
+    }
+
+    /// add an input wire that falls back to `default` if its owner never provides it
+    pub fn add_input_with_default(&mut self, owner: usize, default: F) -> usize {
+        let id = self.add_gate(GateType::Input, None, None, Some(owner));
+        self.gates[id].default = Some(default);
         id
     }
 
@@ -45,6 +122,20 @@ impl Circuit {
             .collect()
     }
 
+    /// how many Input gates each of the n parties owns (0 for pure helpers);
+    /// owners outside 0..n are not counted
+    pub fn input_counts(&self, n: usize) -> Vec<usize> {
+        let mut counts = vec![0; n];
+        for gate in &self.gates {
+            if let (GateType::Input, Some(owner)) = (&gate.gate_type, gate.owner) {
+                if let Some(count) = counts.get_mut(owner) {
+                    *count += 1;
+                }
+            }
+        }
+        counts
+    }
+
     pub fn output_wires(&self) -> Vec<usize> {
         self.gates.iter()
             .filter(|g| matches!(g.gate_type, GateType::Output))
@@ -52,31 +143,479 @@ impl Circuit {
             .collect()
     }
 
+    /// parse a circuit in Bristol fashion
+    ///
+    /// the header gives the gate and wire counts, then the number of input values followed by
+    /// the wire count of each, then the same for outputs. input values occupy the first wires in
+    /// order and outputs the last ones; input value `i` is owned by party `owners[i]`. gate lines
+    /// read `<#in> <#out> <in wires...> <out wire> <OP>`. boolean gates are arithmetized over
+    /// 0/1 values (`AND` → `Mul`, `XOR` → a + b - 2ab, `INV` → 1 - a), `EQW` aliases a wire, and `ADD`/`MUL`
+    /// map directly. every output wire gets an `Output` gate.
+    pub fn from_bristol(reader: impl BufRead, owners: &[usize]) -> Result<Circuit<F>, ParseError> {
+        let mut lines = Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                lines.push((i + 1, line));
+            }
+        }
+        let mut lines = lines.into_iter();
+
+        let mut header = |count_first: bool| -> Result<(usize, Vec<usize>), ParseError> {
+            let (line_no, line) = lines.next().ok_or(ParseError::UnexpectedEof)?;
+            let nums: Vec<usize> = line.split_whitespace()
+                .map(|s| s.parse().map_err(|_| ParseError::InvalidHeader { line: line_no }))
+                .collect::<Result<_, _>>()?;
+            // compare against what's on the line rather than computing k + 1, which a hostile count overflows
+            let valid = if count_first { nums.first().is_some_and(|&k| k == nums.len() - 1) } else { nums.len() == 2 };
+            if !valid {
+                return Err(ParseError::InvalidHeader { line: line_no });
+            }
+            Ok((line_no, nums))
+        };
+        let (_, counts) = header(false)?;
+        let (num_gates, num_wires) = (counts[0], counts[1]);
+        let (inputs_line, inputs) = header(true)?;
+        let (outputs_line, outputs) = header(true)?;
+
+        // both sides name wires, so neither can add up to more than the circuit has
+        let total_width = |line: usize, widths: &[usize]| -> Result<usize, ParseError> {
+            widths.iter().try_fold(0usize, |acc, &w| acc.checked_add(w))
+                .filter(|&total| total <= num_wires)
+                .ok_or(ParseError::InvalidHeader { line })
+        };
+        total_width(inputs_line, &inputs[1..])?;
+        let num_outputs = total_width(outputs_line, &outputs[1..])?;
+
+        let input_values = &inputs[1..];
+        if owners.len() != input_values.len() {
+            return Err(ParseError::OwnerCount { expected: input_values.len(), got: owners.len() });
+        }
+
+        let mut circuit = Circuit::new();
+        let mut wires: HashMap<usize, usize> = HashMap::new(); // bristol wire → gate id
+
+        let mut next_wire = 0;
+        for (&width, &owner) in input_values.iter().zip(owners) {
+            for _ in 0..width {
+                wires.insert(next_wire, circuit.add_gate(GateType::Input, None, None, Some(owner)));
+                next_wire += 1;
+            }
+        }
+
+        let mut parsed = 0;
+        for (line_no, line) in lines {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (op, nums) = fields.split_last().ok_or(ParseError::InvalidGate { line: line_no })?;
+            let nums: Vec<usize> = nums.iter()
+                .map(|s| s.parse().map_err(|_| ParseError::InvalidGate { line: line_no }))
+                .collect::<Result<_, _>>()?;
+            let [n_in, n_out, ..] = nums[..] else { return Err(ParseError::InvalidGate { line: line_no }) };
+            if n_out != 1 || nums.len().checked_sub(2 + n_out) != Some(n_in) {
+                return Err(ParseError::InvalidGate { line: line_no });
+            }
+
+            let ins = nums[2..2 + n_in].iter()
+                .map(|&w| wires.get(&w).copied().ok_or(ParseError::UndefinedWire { line: line_no, wire: w }))
+                .collect::<Result<Vec<_>, _>>()?;
+            let out_wire = nums[2 + n_in];
+
+            let gate = match (*op, &ins[..]) {
+                ("AND" | "MUL", &[a, b]) => circuit.add_gate(GateType::Mul, Some(a), Some(b), None),
+                ("ADD", &[a, b]) => circuit.add_gate(GateType::Add, Some(a), Some(b), None),
+                ("XOR", &[a, b]) => {
+                    let ab = circuit.add_gate(GateType::Mul, Some(a), Some(b), None);
+                    let minus_2ab = circuit.add_gate(GateType::ConstMul(-F::from(2u64)), Some(ab), None, None);
+                    let sum = circuit.add_gate(GateType::Add, Some(a), Some(b), None);
+                    circuit.add_gate(GateType::Add, Some(sum), Some(minus_2ab), None)
+                }
+                ("INV", &[a]) => {
+                    let minus_a = circuit.add_gate(GateType::ConstMul(-F::one()), Some(a), None, None);
+                    circuit.add_gate(GateType::AddConst(F::one()), Some(minus_a), None, None)
+                }
+                ("EQW", &[a]) => a,
+                ("AND" | "MUL" | "ADD" | "XOR" | "INV" | "EQW", _) => return Err(ParseError::InvalidGate { line: line_no }),
+                _ => return Err(ParseError::UnsupportedGate { line: line_no, op: op.to_string() }),
+            };
+            wires.insert(out_wire, gate);
+            parsed += 1;
+        }
+
+        if parsed != num_gates {
+            return Err(ParseError::GateCount { expected: num_gates, got: parsed });
+        }
+
+        for wire in num_wires - num_outputs..num_wires {
+            let gate = *wires.get(&wire).ok_or(ParseError::UndefinedWire { line: outputs_line, wire })?;
+            circuit.add_gate(GateType::Output, Some(gate), None, None);
+        }
+
+        Ok(circuit)
+    }
+
+    /// the minimal standalone circuit computing `root`, i.e. its transitive fan-in
+    ///
+    /// also returns the remapping from gate ids in `self` to gate ids in the subcircuit
+    pub fn subcircuit(&self, root: usize) -> (Circuit<F>, HashMap<usize, usize>) {
+        // collect the fan-in of root
+        let mut needed = vec![false; self.gates.len()];
+        let mut stack = vec![root];
+        while let Some(gate_id) = stack.pop() {
+            if !needed[gate_id] {
+                needed[gate_id] = true;
+                let gate = &self.gates[gate_id];
+                stack.extend([gate.left, gate.right].into_iter().flatten());
+            }
+        }
+
+        // copy it over in dependency order so operands always get lower ids
+        let mut sub = Circuit::new();
+        let mut remap = HashMap::new();
+        for gate_id in self.topological_order() {
+            if !needed[gate_id] {
+                continue;
+            }
+            let gate = &self.gates[gate_id];
+            let new_id = sub.add_gate(
+                gate.gate_type.clone(),
+                gate.left.map(|w| remap[&w]),
+                gate.right.map(|w| remap[&w]),
+                gate.owner,
+            );
+            sub.gates[new_id].default = gate.default;
+            remap.insert(gate_id, new_id);
+        }
+
+        (sub, remap)
+    }
+
+    /// inline both branches and select between their results by the shared bit on `cond`
+    ///
+    /// `then_branch` and `else_branch` are standalone circuits whose i-th Input gate (by gate id)
+    /// reads `inputs[i]` of this circuit, and whose Output gates give the branch results in
+    /// order. the condition is secret, so both branches are always evaluated; each result
+    /// becomes else + cond * (then - else), one multiplication per result. returns the selected
+    /// wires (without Output gates of their own). panics if a branch has more Input gates than
+    /// `inputs` or the branches disagree on their number of outputs.
+    pub fn if_then_else(&mut self, cond: usize, then_branch: &Circuit<F>, else_branch: &Circuit<F>, inputs: &[usize]) -> Vec<usize> {
+        let then_results = self.inline(then_branch, inputs);
+        let else_results = self.inline(else_branch, inputs);
+        assert_eq!(then_results.len(), else_results.len(), "branches have different numbers of outputs");
+
+        then_results.into_iter().zip(else_results).map(|(then_wire, else_wire)| {
+            let minus_else = self.add_gate(GateType::ConstMul(-F::one()), Some(else_wire), None, None);
+            let diff = self.add_gate(GateType::Add, Some(then_wire), Some(minus_else), None);
+            let selected = self.add_gate(GateType::Mul, Some(cond), Some(diff), None);
+            self.add_gate(GateType::Add, Some(else_wire), Some(selected), None)
+        }).collect()
+    }
+
+    /// copy the gates of `other` into this circuit, binding its Input gates to `inputs` in
+    /// order; returns the wires feeding its Output gates
+    fn inline(&mut self, other: &Circuit<F>, inputs: &[usize]) -> Vec<usize> {
+        let mut bound = inputs.iter();
+        let mut remap = HashMap::new(); // gate id in `other` → wire in self
+        for gate in other.gates.iter().filter(|g| matches!(g.gate_type, GateType::Input)) {
+            let &wire = bound.next().expect("branch has more inputs than were given");
+            remap.insert(gate.id, wire);
+        }
+
+        for gate_id in other.topological_order() {
+            let gate = &other.gates[gate_id];
+            let wire = match gate.gate_type {
+                GateType::Input => continue,
+                GateType::Output => remap[&gate.left.expect("Output gate without an operand")],
+                _ => self.add_gate(gate.gate_type.clone(), gate.left.map(|w| remap[&w]), gate.right.map(|w| remap[&w]), gate.owner),
+            };
+            remap.insert(gate_id, wire);
+        }
+        other.output_wires().iter().map(|w| remap[w]).collect()
+    }
+
+    /// every wiring problem in the circuit, or `Ok` if it's safe to hand to the parties
+    ///
+    /// checks that each gate has the operands its type needs and that they're in bounds, that
+    /// each Input has an owner, and that no gate reads from an Output. doesn't look for cycles
+    /// (see `try_topological_order`)
+    pub fn validate(&self) -> Result<(), Vec<CircuitError>> {
+        let mut errors = Vec::new();
+        for gate in &self.gates {
+            let gate_id = gate.id;
+            let (needs_left, needs_right) = match gate.gate_type {
+                GateType::Input => (false, false),
+                GateType::Add | GateType::Mul => (true, true),
+                GateType::ConstMul(_) | GateType::AddConst(_) | GateType::ToAdditive | GateType::ToShamir | GateType::Output => (true, false),
+            };
+            if (needs_left && gate.left.is_none()) || (needs_right && gate.right.is_none()) {
+                errors.push(CircuitError::MissingOperand { gate_id });
+            }
+            if matches!(gate.gate_type, GateType::Input) && gate.owner.is_none() {
+                errors.push(CircuitError::MissingOwner { gate_id });
+            }
+
+            for wire in [gate.left, gate.right].into_iter().flatten() {
+                match self.gates.get(wire) {
+                    None => errors.push(CircuitError::DanglingWire { gate_id, wire }),
+                    Some(operand) if matches!(operand.gate_type, GateType::Output) => {
+                        errors.push(CircuitError::OutputAsOperand { gate_id, output: wire });
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// gates in dependency order (panics on a malformed circuit, see `try_topological_order`)
     pub fn topological_order(&self) -> Vec<usize> {
-        let mut visited = vec![false; self.gates.len()];
+        self.try_topological_order().expect("Malformed circuit")
+    }
+
+    /// gates in dependency order, or an error if the wiring contains a cycle or a dangling wire
+    ///
+    /// uses a three-color dfs: white = unvisited, gray = on the current path, black = finished.
+    /// reaching a gray gate again means we followed a back-edge, i.e. the circuit has a cycle.
+    pub fn try_topological_order(&self) -> Result<Vec<usize>, CircuitError> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Color {
+            White,
+            Gray,
+            Black,
+        }
+
+        let mut color = vec![Color::White; self.gates.len()];
         let mut order = Vec::new();
 
-        fn dfs(gate_id: usize, gates: &[Gate], visited: &mut [bool], order: &mut Vec<usize>) {
-            if visited[gate_id] {
-                return;
+        fn dfs<F>(gate_id: usize, gates: &[Gate<F>], color: &mut [Color], order: &mut Vec<usize>) -> Result<(), CircuitError> {
+            match color[gate_id] {
+                Color::Black => return Ok(()),
+                Color::Gray => return Err(CircuitError::Cycle { gate_id }),
+                Color::White => {}
             }
-            visited[gate_id] = true;
+            color[gate_id] = Color::Gray;
 
             let gate = &gates[gate_id];
-            if let Some(left) = gate.left {
-                dfs(left, gates, visited, order);
-            }
-            if let Some(right) = gate.right {
-                dfs(right, gates, visited, order);
+            for wire in [gate.left, gate.right].into_iter().flatten() {
+                if wire >= gates.len() {
+                    return Err(CircuitError::DanglingWire { gate_id, wire });
+                }
+                dfs(wire, gates, color, order)?;
             }
 
+            color[gate_id] = Color::Black;
             order.push(gate_id);
+            Ok(())
         }
 
         for i in 0..self.gates.len() {
-            dfs(i, &self.gates, &mut visited, &mut order);
+            dfs(i, &self.gates, &mut color, &mut order)?;
+        }
+
+        Ok(order)
+    }
+
+    /// gates grouped into topological levels (panics on a malformed circuit, see `try_layers`)
+    pub fn layers(&self) -> Vec<Vec<usize>> {
+        self.try_layers().expect("Malformed circuit")
+    }
+
+    /// gates grouped into topological levels: inputs are on level 0 and every other gate sits
+    /// one level above its deepest operand, so no gate depends on another gate of its own level
+    pub fn try_layers(&self) -> Result<Vec<Vec<usize>>, CircuitError> {
+        let order = self.try_topological_order()?;
+        let mut depths = vec![0; self.gates.len()];
+        let mut layers: Vec<Vec<usize>> = Vec::new();
+        for g in order {
+            let gate = &self.gates[g];
+            if !matches!(gate.gate_type, GateType::Input) {
+                depths[g] = 1 + [gate.left, gate.right].into_iter().flatten().map(|w| depths[w]).max().unwrap_or(0);
+            }
+            if layers.len() <= depths[g] {
+                layers.resize_with(depths[g] + 1, Vec::new);
+            }
+            layers[depths[g]].push(g);
+        }
+        Ok(layers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn test_topological_order_of_dag() {
+        let mut circuit = Circuit::<Fr>::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let b = circuit.add_gate(GateType::Input, None, None, Some(1));
+        let sum = circuit.add_gate(GateType::Add, Some(a), Some(b), None);
+        let out = circuit.add_gate(GateType::Output, Some(sum), None, None);
+
+        let order = circuit.try_topological_order().unwrap();
+        assert_eq!(order.len(), 4);
+        let pos = |id: usize| order.iter().position(|&g| g == id).unwrap();
+        assert!(pos(a) < pos(sum) && pos(b) < pos(sum) && pos(sum) < pos(out));
+    }
+
+    #[test]
+    fn test_layers_group_independent_gates() {
+        // a * b and c * d don't depend on each other, their sum depends on both
+        let mut circuit = Circuit::<Fr>::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let b = circuit.add_gate(GateType::Input, None, None, Some(1));
+        let c = circuit.add_gate(GateType::Input, None, None, Some(2));
+        let d = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let ab = circuit.add_gate(GateType::Mul, Some(a), Some(b), None);
+        let cd = circuit.add_gate(GateType::Mul, Some(c), Some(d), None);
+        let sum = circuit.add_gate(GateType::Add, Some(ab), Some(cd), None);
+        let out = circuit.add_gate(GateType::Output, Some(sum), None, None);
+
+        let mut layers = circuit.layers();
+        layers.iter_mut().for_each(|layer| layer.sort());
+        assert_eq!(layers, vec![vec![a, b, c, d], vec![ab, cd], vec![sum], vec![out]]);
+    }
+
+    #[test]
+    fn test_input_counts() {
+        // (a + b) * c with a, b, c owned by parties 0, 1, 2
+        let mut circuit = Circuit::<Fr>::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let b = circuit.add_gate(GateType::Input, None, None, Some(1));
+        let c = circuit.add_gate(GateType::Input, None, None, Some(2));
+        let sum = circuit.add_gate(GateType::Add, Some(a), Some(b), None);
+        let product = circuit.add_gate(GateType::Mul, Some(sum), Some(c), None);
+        circuit.add_gate(GateType::Output, Some(product), None, None);
+
+        assert_eq!(circuit.input_counts(5), vec![1, 1, 1, 0, 0]);
+
+        circuit.add_gate(GateType::Input, None, None, Some(2));
+        assert_eq!(circuit.input_counts(3), vec![1, 1, 2]);
+    }
+
+    #[test]
+    fn test_subcircuit_of_mul_gate() {
+        // (a + b) * c, plus an unrelated input and gate
+        let mut circuit = Circuit::<Fr>::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let b = circuit.add_gate(GateType::Input, None, None, Some(1));
+        let unrelated = circuit.add_gate(GateType::Input, None, None, Some(3));
+        let c = circuit.add_gate(GateType::Input, None, None, Some(2));
+        let sum = circuit.add_gate(GateType::Add, Some(a), Some(b), None);
+        let product = circuit.add_gate(GateType::Mul, Some(sum), Some(c), None);
+        circuit.add_gate(GateType::Output, Some(product), None, None);
+        circuit.add_gate(GateType::ConstMul(Fr::from(2u64)), Some(unrelated), None, None);
+
+        let (sub, remap) = circuit.subcircuit(product);
+
+        assert_eq!(sub.gates.len(), 5);
+        assert_eq!(remap.len(), 5);
+        for old in [a, b, c, sum, product] {
+            assert!(remap.contains_key(&old));
         }
+        assert!(!remap.contains_key(&unrelated));
+
+        let new_sum = &sub.gates[remap[&sum]];
+        assert!(matches!(new_sum.gate_type, GateType::Add));
+        assert_eq!((new_sum.left, new_sum.right), (Some(remap[&a]), Some(remap[&b])));
+        assert_eq!(sub.gates[remap[&c]].owner, Some(2));
+        assert_eq!(sub.gates.iter().filter(|g| matches!(g.gate_type, GateType::Input)).count(), 3);
+        assert_eq!(sub.topological_order().len(), 5);
+    }
+
+    #[test]
+    fn test_cycle_is_detected() {
+        let mut circuit = Circuit::<Fr>::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        // gate 2 doesn't exist yet, so wire 1 -> 2 -> 1
+        let x = circuit.add_gate(GateType::Add, Some(a), Some(2), None);
+        circuit.add_gate(GateType::Mul, Some(x), Some(a), None);
+
+        assert!(matches!(circuit.try_topological_order(), Err(CircuitError::Cycle { .. })));
+    }
+
+    #[test]
+    fn test_self_loop_is_detected() {
+        let mut circuit = Circuit::<Fr>::new();
+        circuit.add_gate(GateType::ConstMul(Fr::from(2u64)), Some(0), None, None);
+
+        assert_eq!(circuit.try_topological_order(), Err(CircuitError::Cycle { gate_id: 0 }));
+    }
+
+    #[test]
+    fn test_dangling_wire_is_detected() {
+        let mut circuit = Circuit::<Fr>::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let sum = circuit.add_gate(GateType::Add, Some(a), Some(42), None);
+
+        assert_eq!(
+            circuit.try_topological_order(),
+            Err(CircuitError::DanglingWire { gate_id: sum, wire: 42 })
+        );
+    }
+
+    #[test]
+    fn test_valid_circuit_passes_validation() {
+        let mut circuit = Circuit::<Fr>::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let b = circuit.add_input_with_default(1, Fr::from(3u64));
+        let product = circuit.add_gate(GateType::Mul, Some(a), Some(b), None);
+        let shifted = circuit.add_gate(GateType::AddConst(Fr::from(1u64)), Some(product), None, None);
+        circuit.add_gate(GateType::Output, Some(shifted), None, None);
+
+        assert_eq!(circuit.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_reports_missing_operands() {
+        let mut circuit = Circuit::<Fr>::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let sum = circuit.add_gate(GateType::Add, Some(a), None, None);
+        let scaled = circuit.add_gate(GateType::ConstMul(Fr::from(2u64)), None, None, None);
+        let out = circuit.add_gate(GateType::Output, None, None, None);
+
+        let expected = [sum, scaled, out].map(|gate_id| CircuitError::MissingOperand { gate_id });
+        assert_eq!(circuit.validate(), Err(expected.to_vec()));
+    }
+
+    #[test]
+    fn test_validate_reports_unowned_inputs() {
+        let mut circuit = Circuit::<Fr>::new();
+        let a = circuit.add_gate(GateType::Input, None, None, None);
+        let b = circuit.add_gate(GateType::Input, None, None, Some(1));
+        circuit.add_gate(GateType::Add, Some(a), Some(b), None);
+
+        assert_eq!(circuit.validate(), Err(vec![CircuitError::MissingOwner { gate_id: a }]));
+    }
+
+    #[test]
+    fn test_validate_reports_out_of_bounds_operands() {
+        let mut circuit = Circuit::<Fr>::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let product = circuit.add_gate(GateType::Mul, Some(7), Some(a), None);
+        let out = circuit.add_gate(GateType::Output, Some(9), None, None);
+
+        assert_eq!(circuit.validate(), Err(vec![
+            CircuitError::DanglingWire { gate_id: product, wire: 7 },
+            CircuitError::DanglingWire { gate_id: out, wire: 9 },
+        ]));
+    }
+
+    #[test]
+    fn test_validate_reports_outputs_used_as_operands() {
+        let mut circuit = Circuit::<Fr>::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let out = circuit.add_gate(GateType::Output, Some(a), None, None);
+        let doubled = circuit.add_gate(GateType::Add, Some(out), Some(out), None);
 
-        order
+        assert_eq!(circuit.validate(), Err(vec![
+            CircuitError::OutputAsOperand { gate_id: doubled, output: out },
+            CircuitError::OutputAsOperand { gate_id: doubled, output: out },
+        ]));
     }
 }
@@ -0,0 +1,73 @@
+use ark_ff::{BigInteger, PrimeField};
+
+// fixed-point numbers as field elements: x is represented by round(x * 2^scale_bits), and a
+// negative integer -m by p - m, so additions (and multiplications, after rescaling) of the
+// encodings wrap around modulo p exactly as the signed integers would.
+
+/// the field element representing `value` with `scale_bits` fractional bits
+///
+/// panics unless |value| < 2^int_bits, int_bits + scale_bits <= 126 and the encodings of
+/// both signs are distinct (int_bits + scale_bits < the field's bit size - 1)
+pub fn encode_fixed<F: PrimeField>(value: f64, scale_bits: u32, int_bits: u32) -> F {
+    check_format::<F>(scale_bits, int_bits);
+    let scaled = (value.abs() * 2f64.powi(scale_bits as i32)).round();
+    assert!(scaled < 2f64.powi((int_bits + scale_bits) as i32), "{} does not fit in {} integer bits", value, int_bits);
+
+    let magnitude = F::from(scaled as u128);
+    if value < 0.0 { -magnitude } else { magnitude }
+}
+
+/// the signed fixed-point number a reconstructed field element represents: elements above
+/// (p - 1) / 2 are negative
+///
+/// panics if the magnitude doesn't fit in int_bits + scale_bits bits (the value wrapped or
+/// was never a fixed-point encoding)
+pub fn decode_fixed<F: PrimeField>(value: F, scale_bits: u32, int_bits: u32) -> f64 {
+    check_format::<F>(scale_bits, int_bits);
+    let negative = value.into_bigint() > F::MODULUS_MINUS_ONE_DIV_TWO;
+    let magnitude = if negative { -value } else { value };
+
+    let magnitude = magnitude.into_bigint();
+    assert!(magnitude.num_bits() <= int_bits + scale_bits, "field element is not a fixed-point value");
+    let bytes = magnitude.to_bytes_le();
+    let mut low = [0u8; 16];
+    let len = bytes.len().min(16);
+    low[..len].copy_from_slice(&bytes[..len]);
+    let magnitude = u128::from_le_bytes(low);
+
+    let decoded = magnitude as f64 / 2f64.powi(scale_bits as i32);
+    if negative { -decoded } else { decoded }
+}
+
+fn check_format<F: PrimeField>(scale_bits: u32, int_bits: u32) {
+    assert!(int_bits + scale_bits <= 126 && int_bits + scale_bits + 1 < F::MODULUS_BIT_SIZE, "fixed-point format too wide");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn test_fixed_point_round_trip() {
+        for value in [3.5, -1.25, 0.0, -0.0078125] {
+            assert_eq!(decode_fixed(encode_fixed::<Fr>(value, 16, 16), 16, 16), value);
+        }
+        // -1.25 at scale 2^2 is p - 5
+        assert_eq!(encode_fixed::<Fr>(-1.25, 2, 8), -Fr::from(5u64));
+    }
+
+    #[test]
+    fn test_fixed_point_arithmetic_on_encodings() {
+        let (a, b): (Fr, Fr) = (encode_fixed(3.5, 8, 16), encode_fixed(-1.25, 8, 16));
+        assert_eq!(decode_fixed(a + b, 8, 16), 2.25);
+        // a product carries twice the scale
+        assert_eq!(decode_fixed(a * b, 16, 16), -4.375);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a fixed-point value")]
+    fn test_decoding_an_out_of_range_element_panics() {
+        decode_fixed(encode_fixed::<Fr>(300.0, 8, 16), 8, 4);
+    }
+}
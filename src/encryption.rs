@@ -0,0 +1,115 @@
+use ark_bn254::{Fr, G1Projective};
+use ark_ec::Group;
+use ark_ff::UniformRand;
+use ark_serialize::CanonicalSerialize;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use crate::error::BgwError;
+use crate::party::Party;
+use crate::transport::Transport;
+
+// encrypting outputs for someone outside the computation, with hashed ElGamal in BN254's G1:
+// the sender picks r, publishes r·G, and masks the message with a pad derived from r·pk,
+// which only the holder of sk can recompute as sk·(r·G).
+
+/// A recipient's decryption key
+#[derive(Clone, Debug, PartialEq)]
+pub struct SecretKey(Fr);
+
+/// A recipient's encryption key, sk·G
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PublicKey(pub G1Projective);
+
+/// One field element encrypted under a `PublicKey`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ciphertext {
+    pub ephemeral: G1Projective, // r·G
+    pub masked: Fr,              // message + pad(r·pk)
+}
+
+impl SecretKey {
+    /// a fresh random key
+    pub fn generate() -> SecretKey {
+        SecretKey(Fr::rand(&mut rand::thread_rng()))
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(G1Projective::generator() * self.0)
+    }
+
+    pub fn decrypt(&self, ciphertext: &Ciphertext) -> Fr {
+        ciphertext.masked - pad(ciphertext.ephemeral * self.0)
+    }
+}
+
+impl PublicKey {
+    pub fn encrypt(&self, message: Fr) -> Ciphertext {
+        let r = Fr::rand(&mut rand::thread_rng());
+        Ciphertext {
+            ephemeral: G1Projective::generator() * r,
+            masked: message + pad(self.0 * r),
+        }
+    }
+}
+
+/// the field element a shared point stands for: its compressed encoding seeds a ChaCha20
+/// stream, which works as the key derivation function of hashed ElGamal
+fn pad(shared: G1Projective) -> Fr {
+    let mut seed = [0u8; 32];
+    shared.serialize_compressed(&mut seed[..]).expect("a compressed G1 point is 32 bytes");
+    Fr::rand(&mut ChaCha20Rng::from_seed(seed))
+}
+
+impl<T: Transport<Fr>> Party<Fr, T> {
+    /// Reveal `wire_id` to party `encryptor` only, which encrypts it under `recipient`.
+    ///
+    /// the ciphertext is returned at the encryptor and `None` everywhere else. the encryptor
+    /// sees the plaintext, so it should be a party the recipient trusts with it
+    pub async fn reveal_encrypted(&mut self, wire_id: usize, encryptor: usize, recipient: &PublicKey) -> Result<Option<Ciphertext>, BgwError> {
+        let value = self.open_to(wire_id, encryptor).await?;
+        Ok(value.map(|value| recipient.encrypt(value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::party::tests::network;
+    use crate::sharing::shamir_share;
+
+    #[test]
+    fn test_decrypt_inverts_encrypt() {
+        let sk = SecretKey::generate();
+        let message = Fr::from(1234u64);
+        let ciphertext = sk.public_key().encrypt(message);
+        assert_eq!(sk.decrypt(&ciphertext), message);
+        assert_ne!(ciphertext.masked, message);
+        // a different key gets a different pad
+        assert_ne!(SecretKey::generate().decrypt(&ciphertext), message);
+    }
+
+    #[tokio::test]
+    async fn test_reveal_encrypted_only_to_the_recipient() {
+        let (n, t) = (3, 1);
+        let secret = Fr::from(42u64);
+        let shares = shamir_share(secret, t, n);
+        let sk = SecretKey::generate();
+        let pk = sk.public_key();
+
+        let handles: Vec<_> = network(n, t).into_iter().map(|mut party| {
+            party.shares.insert(0, shares[party.id]);
+            tokio::spawn(async move {
+                let ciphertext = party.reveal_encrypted(0, 2, &pk).await.unwrap();
+                (party, ciphertext)
+            })
+        }).collect();
+
+        let mut results = Vec::new();
+        for h in handles {
+            results.push(h.await.unwrap());
+        }
+        assert!(results[0].1.is_none() && results[1].1.is_none());
+        assert_eq!(sk.decrypt(&results[2].1.unwrap()), secret);
+    }
+}
@@ -0,0 +1,61 @@
+use crate::circuit::CircuitError;
+
+/// Errors a party can run into while executing the protocol
+#[derive(Clone, Debug, PartialEq)]
+pub enum BgwError {
+    MissingInput { wire_id: usize },       // we own this input wire but weren't given a value for it
+    MissingShare { wire_id: usize },       // no share has been computed or received for this wire
+    MissingShares { wire_ids: Vec<usize> }, // input wires still without a share after the input phase
+    MismatchedShares { wire_id: usize },   // operands of this gate are held at different x-coordinates
+    SendFailed { to: usize },
+    ChannelClosed,                         // every sender is gone while we still expect messages
+    Timeout { wire_id: usize },
+    ReconstructFailed { wire_id: usize },  // conflicting shares (same x, different value) for this wire
+    DegreeMismatch,                        // n < 2t + 1, so a degree-2t product can't be reduced
+    InvalidXCoords,                        // evaluation points must be n distinct non-zero values
+    NoTriplesLeft,                         // a Beaver multiplication needs a preprocessed triple
+    EmptyInput,                            // the operation needs at least one wire
+    LengthMismatch { expected: usize, got: usize }, // an operation was given the wrong number of wires
+    InvalidShare { from: usize },          // a dealt share doesn't match the dealer's commitments
+    InsufficientShares { wire_id: usize, got: usize }, // fewer than t+1 output shares arrived in time
+    AssertionFailed { wire_id: usize },    // a secure assertion about this wire's value doesn't hold
+    InvalidBound { bound: u64 },           // a comparison bound must be in [1, i64::MAX]
+    BarrierTimeout { missing: Vec<usize> }, // these parties never reported their input phase done
+    ProtocolAborted(String),               // some party detected an inconsistency and aborted the run
+    Circuit(CircuitError),
+}
+
+impl std::fmt::Display for BgwError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BgwError::MissingInput { wire_id } => write!(f, "no input value for owned wire {}", wire_id),
+            BgwError::MissingShare { wire_id } => write!(f, "missing share for wire {}", wire_id),
+            BgwError::MissingShares { wire_ids } => write!(f, "missing shares for input wires {:?}", wire_ids),
+            BgwError::MismatchedShares { wire_id } => write!(f, "mismatched x-values for wire {}", wire_id),
+            BgwError::SendFailed { to } => write!(f, "failed to send to party {}", to),
+            BgwError::ChannelClosed => write!(f, "channel closed unexpectedly"),
+            BgwError::Timeout { wire_id } => write!(f, "timed out waiting for shares of wire {}", wire_id),
+            BgwError::ReconstructFailed { wire_id } => write!(f, "conflicting shares for wire {}", wire_id),
+            BgwError::DegreeMismatch => write!(f, "need n >= 2t + 1 parties to multiply"),
+            BgwError::InvalidXCoords => write!(f, "need one distinct, non-zero x-coordinate per party"),
+            BgwError::NoTriplesLeft => write!(f, "no Beaver triples left"),
+            BgwError::EmptyInput => write!(f, "no input wires given"),
+            BgwError::LengthMismatch { expected, got } => write!(f, "expected {} wires, got {}", expected, got),
+            BgwError::InsufficientShares { wire_id, got } => write!(f, "only {} shares of output wire {} arrived", got, wire_id),
+            BgwError::InvalidShare { from } => write!(f, "party {} dealt a share that fails verification", from),
+            BgwError::AssertionFailed { wire_id } => write!(f, "assertion about wire {} failed", wire_id),
+            BgwError::InvalidBound { bound } => write!(f, "comparison bound {} is out of range", bound),
+            BgwError::BarrierTimeout { missing } => write!(f, "parties {:?} never finished their input phase", missing),
+            BgwError::ProtocolAborted(reason) => write!(f, "protocol aborted: {}", reason),
+            BgwError::Circuit(e) => write!(f, "malformed circuit: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BgwError {}
+
+impl From<CircuitError> for BgwError {
+    fn from(e: CircuitError) -> Self {
+        BgwError::Circuit(e)
+    }
+}
@@ -0,0 +1,73 @@
+use ark_bn254::{Fq, G1Affine, G1Projective};
+use ark_ff::fields::{Fp64, MontBackend};
+use ark_ff::UniformRand;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use std::sync::OnceLock;
+
+use crate::sharing::{pedersen_commit, pedersen_verify, ProtocolField, Share};
+
+// the fields the protocol can run over. a field needs a group of the same prime order for
+// dealers to commit to their input polynomials in; without one, input shares go unchecked.
+
+/// the public seed `pedersen_h` derives BN254's second generator from
+const PEDERSEN_SEED: &[u8; 32] = b"BGW Pedersen generator for BN254";
+
+/// a second generator h of G1 whose discrete log base g nobody knows: x-coordinates are drawn
+/// from a ChaCha20 stream with a public seed until one is on the curve, and the point with the
+/// smaller y is taken. G1 has cofactor 1, so any curve point other than zero generates it
+pub fn pedersen_h() -> G1Projective {
+    static H: OnceLock<G1Projective> = OnceLock::new();
+    *H.get_or_init(|| {
+        let mut rng = ChaCha20Rng::from_seed(*PEDERSEN_SEED);
+        loop {
+            if let Some(point) = G1Affine::get_point_from_x_unchecked(Fq::rand(&mut rng), false) {
+                return point.into();
+            }
+        }
+    })
+}
+
+/// BN254's scalar field, with Pedersen commitments in G1
+impl ProtocolField for ark_bn254::Fr {
+    type Commitment = G1Projective;
+
+    fn commit(coefficients: &[Self], blinding: &[Self]) -> Vec<G1Projective> {
+        pedersen_commit(pedersen_h(), coefficients, blinding)
+    }
+
+    fn verify(share: &Share<Self>, blinding: Self, commitments: &[G1Projective]) -> bool {
+        pedersen_verify(pedersen_h(), share, blinding, commitments)
+    }
+}
+
+pub use goldilocks::GoldilocksConfig;
+
+#[allow(non_local_definitions)] // ark-ff 0.4's derive expands to an impl inside a function
+mod goldilocks {
+    use ark_ff::fields::MontConfig;
+
+    #[derive(MontConfig)]
+    #[modulus = "18446744069414584321"]
+    #[generator = "7"]
+    pub struct GoldilocksConfig;
+}
+
+/// the 64-bit prime field of order 2^64 - 2^32 + 1, for fast experiments
+///
+/// there's no curve of this order to commit in, so dealers publish empty commitments and
+/// every input share is accepted as dealt: a dealer handing out inconsistent shares goes
+/// unnoticed. use BN254 whenever that matters.
+pub type Goldilocks = Fp64<MontBackend<GoldilocksConfig, 1>>;
+
+impl ProtocolField for Goldilocks {
+    type Commitment = ();
+
+    fn commit(coefficients: &[Self], _blinding: &[Self]) -> Vec<()> {
+        vec![(); coefficients.len()]
+    }
+
+    fn verify(_share: &Share<Self>, _blinding: Self, _commitments: &[()]) -> bool {
+        true
+    }
+}
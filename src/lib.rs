@@ -1,4 +1,11 @@
 pub mod party;
 pub mod circuit;
 pub mod sharing; 
-pub mod message;
\ No newline at end of file
+pub mod message;
+pub mod transport;
+pub mod error;
+pub mod ops;
+pub mod testutil;
+pub mod encoding;
+pub mod fields;
+pub mod encryption;
@@ -1,20 +1,16 @@
 use ark_bn254::Fr;
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::{mpsc, Barrier};
-use tokio::task;
-use tokio::time::{timeout, Duration};
 
 use bgw::circuit::{Circuit, GateType};
-use bgw::party::Party;
-use bgw::message::Message;
+use bgw::party::{Party, PartyConfig};
+use bgw::transport::ChannelTransport;
 
 #[tokio::main]
 async fn main() {
     let n = 5;
     let t = 2;
 
-    // Build circuit: (a + b) * c
+    // Build circuit: a * b + c * d
     let mut circuit = Circuit::new();
 
     let a = circuit.add_gate(GateType::Input, None, None, Some(0));
@@ -27,54 +23,37 @@ async fn main() {
     let product = circuit.add_gate(GateType::Add, Some(mul1), Some(mul2), None);
     let out = circuit.add_gate(GateType::Output, Some(product), None, None);
 
-    // Inputs: party 0 = 2, party 1 = 3, party 2 = 4
+    // Catch wiring mistakes here rather than as a panic inside a party task
+    if let Err(errors) = circuit.validate() {
+        for e in errors {
+            eprintln!("Invalid circuit: {}", e);
+        }
+        return;
+    }
+
+    // Inputs: party 0 = 2, party 1 = 3, party 2 = 4, party 3 = 5
     let inputs: Vec<Fr> = vec![Fr::from(2u64), Fr::from(3u64), Fr::from(4u64), Fr::from(5u64)];
 
     println!("Inputs:");
     println!("Party 0: a = {}", inputs[0]);
     println!("Party 1: b = {}", inputs[1]);
     println!("Party 2: c = {}", inputs[2]);
-    println!("Party 3: no input (helper)");
+    println!("Party 3: d = {}", inputs[3]);
+    println!("Party 4: no input (helper)");
     println!("\nComputing arithmetic circuit...\n");
 
-    // Channel setup
-    let mut party_txs = vec![HashMap::new(); n]; // party_txs[i][j] = tx from i to j
-    let mut inboxes = Vec::with_capacity(n);     // each party's central inbox
-
-    let barrier = Arc::new(Barrier::new(n)); // Barrier for synchronization
-
-    // For each party, create a central inbox (mpsc::Receiver) and a map of txs to all parties
-    for to in 0..n {
-        let (central_tx, central_rx) = mpsc::channel::<Message>(100);
-        inboxes.push(central_rx);
-
-        for from in 0..n {
-            if from != to {
-                let (tx, mut rx) = mpsc::channel::<Message>(100);
-                party_txs[from].insert(to, tx.clone());
-                let central_tx_clone = central_tx.clone();
-                // Forward rx into central_tx
-                task::spawn(async move {
-                    while let Some(msg) = rx.recv().await {
-                        if let Err(_) = central_tx_clone.send(msg).await {
-                            // Channel closed, exit forwarding task
-                            break;
-                        }
-                    }
-                });
-            }
-        }
-        // Add self-sender so each party can send to itself
-        party_txs[to].insert(to, central_tx.clone());
-    }
+    // A fresh id per execution, so nothing from an earlier run is accepted in this one
+    let config = PartyConfig { run_id: rand::random(), ..PartyConfig::new(n, t) };
+
+    // Channel setup: every party can reach every other party (and itself)
+    let transports = ChannelTransport::network(n);
 
     // Launch parties
     let mut handles = vec![];
 
-    for (pid, rx) in inboxes.into_iter().enumerate() {
+    for (pid, transport) in transports.into_iter().enumerate() {
         let circuit_clone = circuit.clone();
-        let mut tx_map = party_txs[pid].clone();
-        let barrier = barrier.clone();
+        let config = config.clone();
 
         let inputs_map = if pid < inputs.len() {
             let mut map = HashMap::new();
@@ -86,37 +65,40 @@ async fn main() {
         };
 
         handles.push(tokio::spawn(async move {
-            let mut party = Party {
-                id: pid,
-                n,
-                t,
-                shares: HashMap::new(),
-                tx: tx_map,
-                rx,
-                barrier,
-            };
-
-            party.input_phase(&circuit_clone, &inputs_map).await;
-            party.evaluate_circuit(&circuit_clone).await;
-            let output = party.output_phase(&[out]).await;
-
-            if let Some(val) = output.get(&out) {
-                println!("Party {} reconstructed output: {}", pid, val);
-            } else {
-                println!("Party {} failed to reconstruct output", pid);
+            let mut party = Party::with_config(pid, config, transport);
+
+            let result = async {
+                party.input_phase(&circuit_clone, &inputs_map).await?;
+                party.input_barrier().await?;
+                party.verify_all_inputs_present(&circuit_clone)?;
+                party.evaluate_circuit(&circuit_clone).await?;
+                party.output_phase(&circuit_clone, &[out]).await
+            }.await;
+
+            match result.map(|output| output.get(&out).copied()) {
+                Ok(Some(val)) => println!("Party {} reconstructed output: {}", pid, val),
+                Ok(None) => println!("Party {} failed to reconstruct output", pid),
+                Err(e) => println!("Party {} aborted: {}", pid, e),
             }
+
+            // hand the party back so its inbox stays open until everyone is done
+            party
         }));
     }
 
     // Wait for all parties
+    let mut finished = Vec::new();
     for (pid, h) in handles.into_iter().enumerate() {
         match h.await {
-            Ok(_) => println!("Party {} completed successfully", pid),
+            Ok(party) => {
+                println!("Party {} completed successfully", pid);
+                finished.push(party);
+            }
             Err(e) => println!("Party {} failed: {:?}", pid, e),
         }
     }
 
     println!("\nVerification:");
-    println!("Expected result: (2 + 3) * 4 = 20");
+    println!("Expected result: 2 * 3 + 4 * 5 = 26");
     println!("All parties should have computed the same result.");
 }
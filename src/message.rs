@@ -1,9 +1,410 @@
-use crate::sharing::Share;
-
-#[derive(Copy, Clone, Debug)]
-pub enum Message {
-    InputShare(usize, Share),
-    MulShare(usize, Share),
-    OutputShare(usize, Share),
-    Reshare(usize, Share)
-}
\ No newline at end of file
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+
+use crate::sharing::{field_bytes, field_from_bytes, field_to_bytes, ProtocolField, Share};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message<F: ProtocolField> {
+    InputShare(usize, Share<F>, F), // (wire_id, share, blinding): the blinding is the recipient's point on the dealer's blinding polynomial
+    DegreeCheck(usize, usize, Share<F>), // (wire_id, index, share): a share of one combination of a product's reshares that must open to zero
+    OutputShare(usize, Share<F>),
+    Reshare(usize, usize, Share<F>), // (wire_id, sender, share): the sender's degree-t sharing for recombination
+    ToShamirShare(usize, Share<F>), // a Shamir share of the sender's additive summand
+    Open(usize, Share<F>), // a share of a masked value being opened to everyone
+    RandShare(usize, Share<F>), // a share of the sender's random contribution to a jointly random value
+    Commitments(usize, Vec<F::Commitment>), // commitments to the dealer's polynomial for an input wire
+    Abort(String), // the sender detected an inconsistency; everyone stops with this reason
+    Ready(usize), // the sender (by id) has dealt all its inputs and received everyone else's
+}
+
+/// A message together with the protocol run it belongs to.
+///
+/// Parties drop envelopes from any other run, so a share replayed from an earlier
+/// execution can't be mistaken for one of the current run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Envelope<F: ProtocolField> {
+    pub run_id: u64,
+    pub msg: Message<F>,
+}
+
+/// errors from decoding a message received off the wire
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodeError {
+    Truncated { len: usize },  // fewer bytes than the message layout requires
+    UnknownTag(u8),
+    NonCanonicalField,         // a field element was not reduced modulo p
+    InvalidPoint,              // a commitment doesn't decode (e.g. not a valid compressed curve point)
+    InvalidUtf8,               // an abort reason is not valid UTF-8
+    TrailingBytes { len: usize },
+    TooLong { len: usize },    // more commitments or a longer abort reason than any party sends
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::Truncated { len } => write!(f, "message truncated at {} bytes", len),
+            DecodeError::UnknownTag(tag) => write!(f, "unknown message tag {}", tag),
+            DecodeError::NonCanonicalField => write!(f, "field element is not canonically encoded"),
+            DecodeError::InvalidPoint => write!(f, "commitment is not a valid curve point"),
+            DecodeError::InvalidUtf8 => write!(f, "abort reason is not valid UTF-8"),
+            DecodeError::TrailingBytes { len } => write!(f, "{} trailing bytes after message", len),
+            DecodeError::TooLong { len } => write!(f, "message field of length {} exceeds its limit", len),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// the most commitments a `Commitments` message carries, i.e. thresholds up to t = 1023
+pub const MAX_COMMITMENTS: usize = 1024;
+
+/// the longest abort reason, in bytes; `Party::abort` cuts longer reasons down to this
+pub const MAX_REASON_LEN: usize = 1024;
+
+// layout: tag (1 byte) | wire_id (u64, little-endian) | share (x, value as little-endian integers,
+// 32 bytes each for BN254)
+// a reshare also carries its sender after the share: ... | sender (u64, little-endian),
+// a degree check its index: ... | index (u64, little-endian),
+// and an input share its blinding: ... | blinding (a field element, like x and value)
+// except for commitments: tag | wire_id | count (u32, little-endian) | count compressed commitments
+// and aborts: tag | length (u32, little-endian) | UTF-8 reason
+const TAG_INPUT_SHARE: u8 = 0;
+const TAG_DEGREE_CHECK: u8 = 1;
+const TAG_OUTPUT_SHARE: u8 = 2;
+const TAG_RESHARE: u8 = 3;
+const TAG_TO_SHAMIR_SHARE: u8 = 4;
+const TAG_OPEN: u8 = 5;
+const TAG_COMMITMENTS: u8 = 6;
+const TAG_RAND_SHARE: u8 = 7;
+const TAG_ABORT: u8 = 8;
+const TAG_READY: u8 = 9;
+
+impl<F: ProtocolField> Message<F> {
+    /// the length of a message carrying one share (everything but input shares, reshares,
+    /// degree checks, commitments and aborts)
+    fn share_message_len() -> usize {
+        1 + 8 + Share::<F>::encoded_len()
+    }
+
+    /// the length of `to_bytes()`, without encoding anything
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            Message::InputShare(..) => Self::share_message_len() + field_bytes::<F>(),
+            Message::Reshare(..) | Message::DegreeCheck(..) => Self::share_message_len() + 8,
+            Message::Commitments(_, points) => 1 + 8 + 4 + points.iter().map(|p| p.compressed_size()).sum::<usize>(),
+            Message::Abort(reason) => 1 + 4 + reason.len(),
+            Message::Ready(_) => 1 + 8,
+            _ => Self::share_message_len(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (tag, wire_id, share, suffix) = match *self {
+            Message::InputShare(wire_id, share, blinding) => (TAG_INPUT_SHARE, wire_id, share, field_to_bytes(&blinding)),
+            Message::DegreeCheck(wire_id, index, share) => (TAG_DEGREE_CHECK, wire_id, share, (index as u64).to_le_bytes().to_vec()),
+            Message::OutputShare(wire_id, share) => (TAG_OUTPUT_SHARE, wire_id, share, Vec::new()),
+            Message::Reshare(wire_id, from, share) => (TAG_RESHARE, wire_id, share, (from as u64).to_le_bytes().to_vec()),
+            Message::ToShamirShare(wire_id, share) => (TAG_TO_SHAMIR_SHARE, wire_id, share, Vec::new()),
+            Message::Open(wire_id, share) => (TAG_OPEN, wire_id, share, Vec::new()),
+            Message::RandShare(wire_id, share) => (TAG_RAND_SHARE, wire_id, share, Vec::new()),
+            Message::Commitments(wire_id, ref points) => {
+                let mut bytes = Vec::with_capacity(self.encoded_len());
+                bytes.push(TAG_COMMITMENTS);
+                bytes.extend_from_slice(&(wire_id as u64).to_le_bytes());
+                bytes.extend_from_slice(&(points.len() as u32).to_le_bytes());
+                for point in points {
+                    point.serialize_compressed(&mut bytes).expect("writing to a Vec can't fail");
+                }
+                return bytes;
+            }
+            Message::Abort(ref reason) => {
+                let mut bytes = Vec::with_capacity(1 + 4 + reason.len());
+                bytes.push(TAG_ABORT);
+                bytes.extend_from_slice(&(reason.len() as u32).to_le_bytes());
+                bytes.extend_from_slice(reason.as_bytes());
+                return bytes;
+            }
+            Message::Ready(from) => {
+                let mut bytes = vec![TAG_READY];
+                bytes.extend_from_slice(&(from as u64).to_le_bytes());
+                return bytes;
+            }
+        };
+
+        let mut bytes = Vec::with_capacity(self.encoded_len());
+        bytes.push(tag);
+        bytes.extend_from_slice(&(wire_id as u64).to_le_bytes());
+        bytes.extend_from_slice(&share.to_bytes());
+        bytes.extend_from_slice(&suffix);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Message<F>, DecodeError> {
+        match bytes.first() {
+            Some(&TAG_COMMITMENTS) => return Self::commitments_from_bytes(bytes),
+            Some(&TAG_ABORT) => return Self::abort_from_bytes(bytes),
+            Some(&TAG_READY) => return Self::ready_from_bytes(bytes),
+            _ => {}
+        }
+        let share_end = Self::share_message_len();
+        let expected = match bytes.first() {
+            Some(&TAG_INPUT_SHARE) => share_end + field_bytes::<F>(),
+            Some(&(TAG_RESHARE | TAG_DEGREE_CHECK)) => share_end + 8,
+            _ => share_end,
+        };
+        if bytes.len() < expected {
+            return Err(DecodeError::Truncated { len: bytes.len() });
+        }
+        if bytes.len() > expected {
+            return Err(DecodeError::TrailingBytes { len: bytes.len() - expected });
+        }
+
+        let wire_id = u64::from_le_bytes(bytes[1..9].try_into().unwrap()) as usize;
+        let share = Share::from_bytes(&bytes[9..share_end]).ok_or(DecodeError::NonCanonicalField)?;
+
+        match bytes[0] {
+            TAG_INPUT_SHARE => {
+                let blinding = field_from_bytes(&bytes[share_end..]).ok_or(DecodeError::NonCanonicalField)?;
+                Ok(Message::InputShare(wire_id, share, blinding))
+            }
+            TAG_OUTPUT_SHARE => Ok(Message::OutputShare(wire_id, share)),
+            TAG_RESHARE => {
+                let from = u64::from_le_bytes(bytes[share_end..].try_into().unwrap()) as usize;
+                Ok(Message::Reshare(wire_id, from, share))
+            }
+            TAG_DEGREE_CHECK => {
+                let index = u64::from_le_bytes(bytes[share_end..].try_into().unwrap()) as usize;
+                Ok(Message::DegreeCheck(wire_id, index, share))
+            }
+            TAG_TO_SHAMIR_SHARE => Ok(Message::ToShamirShare(wire_id, share)),
+            TAG_OPEN => Ok(Message::Open(wire_id, share)),
+            TAG_RAND_SHARE => Ok(Message::RandShare(wire_id, share)),
+            tag => Err(DecodeError::UnknownTag(tag)),
+        }
+    }
+
+    fn commitments_from_bytes(bytes: &[u8]) -> Result<Message<F>, DecodeError> {
+        const HEADER: usize = 1 + 8 + 4;
+        if bytes.len() < HEADER {
+            return Err(DecodeError::Truncated { len: bytes.len() });
+        }
+        let wire_id = u64::from_le_bytes(bytes[1..9].try_into().unwrap()) as usize;
+        let count = u32::from_le_bytes(bytes[9..HEADER].try_into().unwrap()) as usize;
+        if count > MAX_COMMITMENTS {
+            return Err(DecodeError::TooLong { len: count });
+        }
+
+        // deserialize_compressed also checks a curve point is on the curve and in the subgroup
+        let mut body = &bytes[HEADER..];
+        let mut points = Vec::new();
+        for _ in 0..count {
+            match F::Commitment::deserialize_compressed(&mut body) {
+                Ok(point) => points.push(point),
+                Err(SerializationError::IoError(_)) => return Err(DecodeError::Truncated { len: bytes.len() }),
+                Err(_) => return Err(DecodeError::InvalidPoint),
+            }
+        }
+        if !body.is_empty() {
+            return Err(DecodeError::TrailingBytes { len: body.len() });
+        }
+        Ok(Message::Commitments(wire_id, points))
+    }
+
+    fn ready_from_bytes(bytes: &[u8]) -> Result<Message<F>, DecodeError> {
+        match bytes.len() {
+            len if len < 1 + 8 => Err(DecodeError::Truncated { len }),
+            len if len > 1 + 8 => Err(DecodeError::TrailingBytes { len: len - (1 + 8) }),
+            _ => Ok(Message::Ready(u64::from_le_bytes(bytes[1..].try_into().unwrap()) as usize)),
+        }
+    }
+
+    fn abort_from_bytes(bytes: &[u8]) -> Result<Message<F>, DecodeError> {
+        const HEADER: usize = 1 + 4;
+        if bytes.len() < HEADER {
+            return Err(DecodeError::Truncated { len: bytes.len() });
+        }
+        let len = u32::from_le_bytes(bytes[1..HEADER].try_into().unwrap()) as usize;
+        if len > MAX_REASON_LEN {
+            return Err(DecodeError::TooLong { len });
+        }
+
+        let body = &bytes[HEADER..];
+        if body.len() < len {
+            return Err(DecodeError::Truncated { len: bytes.len() });
+        }
+        if body.len() > len {
+            return Err(DecodeError::TrailingBytes { len: body.len() - len });
+        }
+        let reason = String::from_utf8(body.to_vec()).map_err(|_| DecodeError::InvalidUtf8)?;
+        Ok(Message::Abort(reason))
+    }
+}
+
+impl<F: ProtocolField> Envelope<F> {
+    /// the length of `to_bytes()`
+    pub fn encoded_len(&self) -> usize {
+        8 + self.msg.encoded_len()
+    }
+
+    /// an upper bound on `encoded_len()` for any envelope that decodes: the larger of a
+    /// reshare, `MAX_COMMITMENTS` commitments and an abort with a `MAX_REASON_LEN` reason
+    pub fn max_encoded_len() -> usize {
+        let commitment_len = F::commit(&[F::zero()], &[F::zero()])[0].compressed_size();
+        let share = Message::<F>::share_message_len() + field_bytes::<F>().max(8);
+        let commitments = 1 + 8 + 4 + MAX_COMMITMENTS * commitment_len;
+        let abort = 1 + 4 + MAX_REASON_LEN;
+        8 + share.max(commitments).max(abort)
+    }
+
+    /// the run id (u64, little-endian) followed by the message's own encoding
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.run_id.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&self.msg.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Envelope<F>, DecodeError> {
+        if bytes.len() < 8 {
+            return Err(DecodeError::Truncated { len: bytes.len() });
+        }
+        let (run_id, msg) = bytes.split_at(8);
+        Ok(Envelope {
+            run_id: u64::from_le_bytes(run_id.try_into().unwrap()),
+            msg: Message::from_bytes(msg)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Fr, G1Projective};
+    use ark_ec::Group;
+    use ark_ff::{UniformRand, Zero};
+
+    const MESSAGE_BYTES: usize = 1 + 8 + 64; // tag, wire id and a share of two 32-byte elements
+    const POINT_BYTES: usize = 32; // compressed G1 point
+
+    #[test]
+    fn test_every_variant_round_trips() {
+        let mut rng = rand::thread_rng();
+        // -1 and -2 are p-1 and p-2, the largest canonical encodings
+        let values = [Fr::from(0u64), -Fr::from(1u64), -Fr::from(2u64), Fr::rand(&mut rng)];
+
+        for &value in &values {
+            let share = Share { x: -Fr::from(1u64), value };
+            let messages = [
+                Message::InputShare(0, share, value),
+                Message::DegreeCheck(7, 2, share),
+                Message::OutputShare(usize::MAX, share),
+                Message::Reshare(123_456, 4, share),
+                Message::ToShamirShare(1, share),
+                Message::Open(2, share),
+                Message::RandShare(3, share),
+            ];
+            for msg in messages {
+                let bytes = msg.to_bytes();
+                let extra = match msg {
+                    Message::InputShare(..) => 32,
+                    Message::Reshare(..) | Message::DegreeCheck(..) => 8,
+                    _ => 0,
+                };
+                assert_eq!(bytes.len(), MESSAGE_BYTES + extra);
+                assert_eq!(bytes.len(), msg.encoded_len());
+                let decoded = Message::from_bytes(&bytes).unwrap();
+                assert_eq!(decoded, msg);
+                assert_eq!(decoded.to_bytes(), bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn test_malformed_bytes_are_rejected() {
+        let share = Share { x: Fr::from(1u64), value: Fr::from(2u64) };
+        let bytes = Message::OutputShare(3, share).to_bytes();
+
+        assert_eq!(Message::<Fr>::from_bytes(&bytes[..10]), Err(DecodeError::Truncated { len: 10 }));
+
+        let mut unknown = bytes.clone();
+        unknown[0] = 42;
+        assert_eq!(Message::<Fr>::from_bytes(&unknown), Err(DecodeError::UnknownTag(42)));
+
+        // all-ones is far above the modulus
+        let mut unreduced = bytes.clone();
+        unreduced[9 + 32..].fill(0xff);
+        assert_eq!(Message::<Fr>::from_bytes(&unreduced), Err(DecodeError::NonCanonicalField));
+    }
+
+    #[test]
+    fn test_commitments_round_trip() {
+        let g = G1Projective::generator();
+        for points in [vec![], vec![g], vec![g * Fr::from(3u64), G1Projective::zero(), g * Fr::rand(&mut rand::thread_rng())]] {
+            let msg: Message<Fr> = Message::Commitments(9, points.clone());
+            let bytes = msg.to_bytes();
+            assert_eq!(bytes.len(), 1 + 8 + 4 + points.len() * POINT_BYTES);
+            assert_eq!(bytes.len(), msg.encoded_len());
+            assert_eq!(Message::from_bytes(&bytes), Ok(msg));
+
+            assert_eq!(Message::<Fr>::from_bytes(&bytes[..bytes.len() - 1]), Err(DecodeError::Truncated { len: bytes.len() - 1 }));
+        }
+
+        let mut bytes = Message::<Fr>::Commitments(9, vec![g]).to_bytes();
+        bytes.push(0);
+        assert_eq!(Message::<Fr>::from_bytes(&bytes), Err(DecodeError::TrailingBytes { len: 1 }));
+
+        // an x-coordinate that is not reduced modulo the base field
+        let mut bad = Message::<Fr>::Commitments(9, vec![g]).to_bytes();
+        bad[13..].fill(0x3f);
+        assert_eq!(Message::<Fr>::from_bytes(&bad), Err(DecodeError::InvalidPoint));
+    }
+
+    #[test]
+    fn test_abort_round_trip() {
+        for reason in ["", "conflicting shares for wire 3", "ünïcödé"] {
+            let msg = Message::<Fr>::Abort(reason.to_string());
+            let bytes = msg.to_bytes();
+            assert_eq!(bytes.len(), msg.encoded_len());
+            assert_eq!(Message::from_bytes(&bytes), Ok(msg));
+        }
+
+        let mut bytes = Message::<Fr>::Abort("x".to_string()).to_bytes();
+        bytes[5] = 0xff;
+        assert_eq!(Message::<Fr>::from_bytes(&bytes), Err(DecodeError::InvalidUtf8));
+        bytes.push(0);
+        assert_eq!(Message::<Fr>::from_bytes(&bytes), Err(DecodeError::TrailingBytes { len: 1 }));
+    }
+
+    #[test]
+    fn test_oversized_fields_are_rejected() {
+        let mut bytes = Message::<Fr>::Abort("x".repeat(MAX_REASON_LEN)).to_bytes();
+        assert!(Message::<Fr>::from_bytes(&bytes).is_ok());
+        bytes[1..5].copy_from_slice(&(MAX_REASON_LEN as u32 + 1).to_le_bytes());
+        assert_eq!(Message::<Fr>::from_bytes(&bytes), Err(DecodeError::TooLong { len: MAX_REASON_LEN + 1 }));
+
+        let mut bytes = Message::<Fr>::Commitments(0, Vec::new()).to_bytes();
+        bytes[9..13].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(Message::<Fr>::from_bytes(&bytes), Err(DecodeError::TooLong { len: u32::MAX as usize }));
+
+        let largest = Envelope { run_id: 0, msg: Message::<Fr>::Commitments(0, vec![G1Projective::generator(); MAX_COMMITMENTS]) };
+        assert!(largest.encoded_len() <= Envelope::<Fr>::max_encoded_len());
+    }
+
+    #[test]
+    fn test_ready_round_trip() {
+        let msg = Message::<Fr>::Ready(4);
+        let bytes = msg.to_bytes();
+        assert_eq!(bytes.len(), msg.encoded_len());
+        assert_eq!(Message::from_bytes(&bytes), Ok(msg));
+        assert_eq!(Message::<Fr>::from_bytes(&bytes[..5]), Err(DecodeError::Truncated { len: 5 }));
+    }
+
+    #[test]
+    fn test_envelope_round_trip() {
+        let share = Share { x: Fr::from(3u64), value: Fr::from(4u64) };
+        let envelope = Envelope { run_id: u64::MAX - 1, msg: Message::OutputShare(5, share) };
+        let bytes = envelope.to_bytes();
+        assert_eq!(bytes.len(), 8 + MESSAGE_BYTES);
+        assert_eq!(bytes.len(), envelope.encoded_len());
+        assert_eq!(Envelope::from_bytes(&bytes), Ok(envelope));
+        assert_eq!(Envelope::<Fr>::from_bytes(&bytes[..5]), Err(DecodeError::Truncated { len: 5 }));
+    }
+}
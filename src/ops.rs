@@ -0,0 +1,754 @@
+use ark_ff::PrimeField;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use rand::seq::SliceRandom;
+
+use crate::error::BgwError;
+use crate::party::Party;
+use crate::sharing::{ProtocolField, Share};
+use crate::transport::Transport;
+
+// secure operations built on top of the gate primitives. they work on shares held in
+// `Party::shares` and keep their intermediate values on scratch wires, so every party
+// must run the same operations in the same order to agree on the wire ids.
+//
+// the comparisons (`less_than`, `is_zero` and everything built on them) take a public
+// `bound` and are only defined for integers in [0, bound): they evaluate a public polynomial
+// of degree 2 * bound - 2 that maps every difference in (-bound, bound) to 0/1, and give a
+// garbage share for anything outside it. nothing can check this without revealing the
+// values, so it is the caller's precondition. the powers of the polynomial are built by
+// doubling, so a comparison costs ⌈log2(2 * bound - 2)⌉ rounds and 2 * bound - 3 products.
+
+/// scratch wires are numbered from here so they never collide with circuit gate ids
+const SCRATCH_WIRE_BASE: usize = usize::MAX / 2;
+
+/// A secret permutation of k positions, as shares of its k×k 0/1 matrix: entry (i, j) is 1
+/// iff output i takes input j. Built once by `random_permutation`, it can be applied to any
+/// number of vectors with `apply_permutation`.
+#[derive(Clone, Debug)]
+pub struct SharedPermutation<F> {
+    matrix: Vec<Vec<Share<F>>>, // matrix[i][j]
+}
+
+impl<F> SharedPermutation<F> {
+    /// the number of positions permuted
+    pub fn len(&self) -> usize {
+        self.matrix.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.matrix.is_empty()
+    }
+}
+
+impl<F: ProtocolField, T: Transport<F>> Party<F, T> {
+    /// a fresh wire id for an intermediate value
+    pub(crate) fn scratch_wire(&mut self) -> usize {
+        let wire_id = SCRATCH_WIRE_BASE + self.next_scratch_wire;
+        self.next_scratch_wire += 1;
+        wire_id
+    }
+
+    /// the sharing of a public constant (the degree-0 polynomial f(x) = c)
+    pub fn constant(&self, c: F) -> Share<F> {
+        Share { x: self.x(), value: c }
+    }
+
+    /// local `constant + sum_i weight_i * share_i`
+    pub(crate) fn affine(&self, terms: &[(Share<F>, F)], constant: F) -> Share<F> {
+        let value = terms.iter().fold(constant, |acc, &(s, w)| acc + s.value * w);
+        Share { x: self.x(), value }
+    }
+
+    /// multiply two shares with a Mul gate on scratch wires (using a Beaver triple if one is left)
+    pub async fn mul_shares(&mut self, a: Share<F>, b: Share<F>) -> Result<Share<F>, BgwError> {
+        let (wa, wb, out) = (self.scratch_wire(), self.scratch_wire(), self.scratch_wire());
+        self.shares.insert(wa, a);
+        self.shares.insert(wb, b);
+        if self.triples.is_empty() {
+            self.eval_mul(out, wa, wb).await?;
+        } else {
+            self.eval_mul_beaver(out, wa, wb).await?;
+        }
+        self.shares.remove(&wa);
+        self.shares.remove(&wb);
+        self.shares.remove(&out).ok_or(BgwError::MissingShare { wire_id: out })
+    }
+
+    /// multiply each pair of shares; without Beaver triples all the products share one
+    /// `eval_mul_batch`, i.e. one round in total
+    pub async fn mul_shares_batch(&mut self, pairs: &[(Share<F>, Share<F>)]) -> Result<Vec<Share<F>>, BgwError> {
+        if !self.triples.is_empty() {
+            let mut products = Vec::with_capacity(pairs.len());
+            for &(a, b) in pairs {
+                products.push(self.mul_shares(a, b).await?);
+            }
+            return Ok(products);
+        }
+
+        let mut gates = Vec::with_capacity(pairs.len());
+        for &(a, b) in pairs {
+            let (wa, wb, out) = (self.scratch_wire(), self.scratch_wire(), self.scratch_wire());
+            self.shares.insert(wa, a);
+            self.shares.insert(wb, b);
+            gates.push((out, wa, wb));
+        }
+        self.eval_mul_batch(&gates).await?;
+
+        gates.iter().map(|&(out, wa, wb)| {
+            self.shares.remove(&wa);
+            self.shares.remove(&wb);
+            self.shares.remove(&out).ok_or(BgwError::MissingShare { wire_id: out })
+        }).collect()
+    }
+
+    /// the product of `values` by a balanced tree: ⌈log k⌉ levels of batched multiplications
+    async fn product_tree(&mut self, mut values: Vec<Share<F>>) -> Result<Share<F>, BgwError> {
+        while values.len() > 1 {
+            let pairs: Vec<(Share<F>, Share<F>)> = values.chunks_exact(2).map(|p| (p[0], p[1])).collect();
+            let odd = (values.len() % 2 == 1).then(|| values[values.len() - 1]);
+            values = self.mul_shares_batch(&pairs).await?;
+            values.extend(odd);
+        }
+        values.pop().ok_or(BgwError::EmptyInput)
+    }
+
+    /// a sharing of the AND of the 0/1 values on `wires`: their product
+    pub async fn bit_and_many(&mut self, wires: &[usize]) -> Result<Share<F>, BgwError> {
+        let bits = wires.iter().map(|&w| self.share(w)).collect::<Result<Vec<_>, _>>()?;
+        self.product_tree(bits).await
+    }
+
+    /// a sharing of the OR of the 0/1 values on `wires`: 1 - prod(1 - b_i)
+    pub async fn bit_or_many(&mut self, wires: &[usize]) -> Result<Share<F>, BgwError> {
+        let negated = wires.iter()
+            .map(|&w| Ok(self.affine(&[(self.share(w)?, -F::one())], F::one())))
+            .collect::<Result<Vec<_>, BgwError>>()?;
+        let none_set = self.product_tree(negated).await?;
+        Ok(self.affine(&[(none_set, -F::one())], F::one()))
+    }
+
+    /// a sharing of a AND b for 0/1 values a and b: their product
+    pub async fn bit_and(&mut self, a: Share<F>, b: Share<F>) -> Result<Share<F>, BgwError> {
+        self.mul_shares(a, b).await
+    }
+
+    /// a sharing of a XOR b for 0/1 values a and b: a + b - 2ab, one multiplication
+    pub async fn bit_xor(&mut self, a: Share<F>, b: Share<F>) -> Result<Share<F>, BgwError> {
+        let ab = self.bit_and(a, b).await?;
+        Ok(self.xor_given_and(a, b, ab))
+    }
+
+    /// a XOR b when a AND b is already shared (local)
+    fn xor_given_and(&self, a: Share<F>, b: Share<F>, ab: Share<F>) -> Share<F> {
+        self.affine(&[(a, F::one()), (b, F::one()), (ab, -F::from(2u64))], F::zero())
+    }
+
+    /// Sum of the k-bit numbers on `a_bits` and `b_bits` (least significant bit first, 0/1
+    /// values), as k + 1 shared bits with the final carry last.
+    ///
+    /// Each bit goes through a full adder: with t = a XOR b, sum = t XOR carry and the new
+    /// carry is ab + t * carry (the two terms are never both 1). The k products ab are
+    /// independent and share one batch; the products t * carry form a chain, so the
+    /// multiplicative depth is k (O(k)), as for any ripple-carry adder. Fails with
+    /// `LengthMismatch` unless both operands have the same number of bits.
+    pub async fn ripple_carry_add(&mut self, a_bits: &[usize], b_bits: &[usize]) -> Result<Vec<Share<F>>, BgwError> {
+        if a_bits.len() != b_bits.len() {
+            return Err(BgwError::LengthMismatch { expected: a_bits.len(), got: b_bits.len() });
+        }
+        let a = a_bits.iter().map(|&w| self.share(w)).collect::<Result<Vec<_>, _>>()?;
+        let b = b_bits.iter().map(|&w| self.share(w)).collect::<Result<Vec<_>, _>>()?;
+        let pairs: Vec<(Share<F>, Share<F>)> = a.iter().copied().zip(b.iter().copied()).collect();
+        let ands = self.mul_shares_batch(&pairs).await?;
+
+        let mut sum = Vec::with_capacity(a.len() + 1);
+        let mut carry = self.constant(F::zero());
+        for (i, (&(ai, bi), &ab)) in pairs.iter().zip(&ands).enumerate() {
+            let t = self.xor_given_and(ai, bi, ab);
+            if i == 0 {
+                // no carry in yet
+                sum.push(t);
+                carry = ab;
+                continue;
+            }
+            let t_carry = self.bit_and(t, carry).await?;
+            sum.push(self.xor_given_and(t, carry, t_carry));
+            carry = self.affine(&[(ab, F::one()), (t_carry, F::one())], F::zero());
+        }
+        sum.push(carry);
+        Ok(sum)
+    }
+
+    /// Check that the value on `wire` is 0 or 1 without revealing which, failing with
+    /// `AssertionFailed` otherwise.
+    ///
+    /// b(b - 1) is 0 exactly when b is a bit; it is multiplied by a fresh shared random r
+    /// before being opened, so a failing check shows only a uniformly random non-zero value
+    /// rather than b(b - 1) itself. a non-bit passes only if r = 0, with probability 1/p.
+    pub async fn assert_bit(&mut self, wire: usize) -> Result<(), BgwError> {
+        let b = self.share(wire)?;
+        let b_minus_1 = self.affine(&[(b, F::one())], -F::one());
+        let r_wire = self.scratch_wire();
+        self.shared_random(r_wire).await?;
+        let r = self.shares.remove(&r_wire).ok_or(BgwError::MissingShare { wire_id: r_wire })?;
+
+        let check = self.product_tree(vec![r, b, b_minus_1]).await?;
+        let check_wire = self.scratch_wire();
+        self.shares.insert(check_wire, check);
+        let opened = self.open(check_wire).await;
+        self.shares.remove(&check_wire);
+        if opened?.is_zero() {
+            Ok(())
+        } else {
+            Err(BgwError::AssertionFailed { wire_id: wire })
+        }
+    }
+
+    /// x, x^2, ..., x^degree for each of `xs`, by doubling: once x^1..x^k are known, x^k times
+    /// each of them gives x^(k+1)..x^(2k). ⌈log2 degree⌉ rounds, with all the products of a
+    /// round (for every x) in one `mul_shares_batch`
+    async fn powers(&mut self, xs: &[Share<F>], degree: usize) -> Result<Vec<Vec<Share<F>>>, BgwError> {
+        let mut powers: Vec<Vec<Share<F>>> = xs.iter().map(|&x| vec![x]).collect(); // powers[i][j] = xs[i]^(j + 1)
+        let mut known = 1;
+        while known < degree {
+            let next = known.min(degree - known);
+            let pairs: Vec<(Share<F>, Share<F>)> = powers.iter()
+                .flat_map(|p| p[..next].iter().map(|&low| (low, p[known - 1])))
+                .collect();
+            let products = self.mul_shares_batch(&pairs).await?;
+            for (p, higher) in powers.iter_mut().zip(products.chunks(next)) {
+                p.extend_from_slice(higher);
+            }
+            known += next;
+        }
+        for p in &mut powers {
+            p.truncate(degree);
+        }
+        Ok(powers)
+    }
+
+    /// evaluate the public polynomial `sum_j coeffs[j] * x^j` on each shared x in `xs`, all of
+    /// them sharing the rounds of one `powers`
+    pub(crate) async fn eval_public_poly(&mut self, xs: &[Share<F>], coeffs: &[F]) -> Result<Vec<Share<F>>, BgwError> {
+        let constant = coeffs.first().copied().unwrap_or_else(F::zero);
+        let powers = self.powers(xs, coeffs.len().saturating_sub(1)).await?;
+        Ok(powers.iter().map(|p| {
+            let terms: Vec<(Share<F>, F)> = p.iter().copied().zip(coeffs.iter().skip(1).copied()).collect();
+            self.affine(&terms, constant)
+        }).collect())
+    }
+
+    /// a sharing of 1 if a < b, else 0, for a and b in [0, bound)
+    ///
+    /// fails with `InvalidBound` unless 1 <= bound <= i64::MAX
+    pub async fn less_than(&mut self, a: Share<F>, b: Share<F>, bound: u64) -> Result<Share<F>, BgwError> {
+        let coeffs = indicator_polynomial::<F>("less_than", bound, |d| d < 0)?;
+        let diff = self.affine(&[(a, F::one()), (b, -F::one())], F::zero());
+        Ok(self.eval_public_poly(&[diff], &coeffs).await?.remove(0))
+    }
+
+    /// a sharing of 1 if x = 0, else 0, for x in (-bound, bound)
+    ///
+    /// fails with `InvalidBound` unless 1 <= bound <= i64::MAX
+    pub async fn is_zero(&mut self, x: Share<F>, bound: u64) -> Result<Share<F>, BgwError> {
+        let coeffs = indicator_polynomial::<F>("is_zero", bound, |d| d == 0)?;
+        Ok(self.eval_public_poly(&[x], &coeffs).await?.remove(0))
+    }
+
+    /// Shared count of the labels on `label_wires` equal to each public category, in order.
+    ///
+    /// One `is_zero(label - category)` per (label, category) pair, summed locally per category;
+    /// all of them share the rounds of a single `is_zero`. Labels and categories must lie in
+    /// [0, bound).
+    pub async fn histogram(&mut self, label_wires: &[usize], categories: &[F], bound: u64) -> Result<Vec<Share<F>>, BgwError> {
+        let labels = label_wires.iter().map(|&w| self.share(w)).collect::<Result<Vec<_>, _>>()?;
+        let coeffs = indicator_polynomial::<F>("is_zero", bound, |d| d == 0)?;
+
+        let diffs: Vec<Share<F>> = categories.iter()
+            .flat_map(|&category| labels.iter().map(move |&label| (label, category)))
+            .map(|(label, category)| self.affine(&[(label, F::one())], -category))
+            .collect();
+        let matches = self.eval_public_poly(&diffs, &coeffs).await?;
+
+        let k = labels.len();
+        Ok((0..categories.len()).map(|c| {
+            let terms: Vec<(Share<F>, F)> = matches[c * k..(c + 1) * k].iter().map(|&s| (s, F::one())).collect();
+            self.affine(&terms, F::zero())
+        }).collect())
+    }
+
+    /// Shared maximum of the values on `wires` and the (0-based) position of its first occurrence.
+    ///
+    /// A linear scan keeping a running (max, index) pair: k - 1 comparisons, each followed by
+    /// two multiplexing multiplications, so the depth grows linearly with the number of wires.
+    /// Values must lie in [0, bound).
+    pub async fn max_with_index(&mut self, wires: &[usize], bound: u64) -> Result<(Share<F>, Share<F>), BgwError> {
+        let (&first, rest) = wires.split_first().ok_or(BgwError::EmptyInput)?;
+        let mut max = self.share(first)?;
+        let mut index = self.constant(F::zero());
+
+        for (i, &wire_id) in rest.iter().enumerate() {
+            let value = self.share(wire_id)?;
+            let bigger = self.less_than(max, value, bound).await?;
+
+            // max += bigger * (value - max), index += bigger * (i + 1 - index)
+            let value_minus_max = self.affine(&[(value, F::one()), (max, -F::one())], F::zero());
+            let delta = self.mul_shares(bigger, value_minus_max).await?;
+            max = self.affine(&[(max, F::one()), (delta, F::one())], F::zero());
+
+            let position_minus_index = self.affine(&[(index, -F::one())], F::from((i + 1) as u64));
+            let delta = self.mul_shares(bigger, position_minus_index).await?;
+            index = self.affine(&[(index, F::one()), (delta, F::one())], F::zero());
+        }
+
+        Ok((max, index))
+    }
+
+    /// (min(a, b), max(a, b)): one comparison and one multiplication. a and b must lie in
+    /// [0, bound).
+    pub async fn compare_and_swap(&mut self, a: Share<F>, b: Share<F>, bound: u64) -> Result<(Share<F>, Share<F>), BgwError> {
+        let swap = self.less_than(b, a, bound).await?;
+
+        // min = a + swap * (b - a), max = b - swap * (b - a)
+        let b_minus_a = self.affine(&[(b, F::one()), (a, -F::one())], F::zero());
+        let delta = self.mul_shares(swap, b_minus_a).await?;
+        let min = self.affine(&[(a, F::one()), (delta, F::one())], F::zero());
+        let max = self.affine(&[(b, F::one()), (delta, -F::one())], F::zero());
+        Ok((min, max))
+    }
+
+    /// Shared minimum of the values on `wires`, by a tournament tree: each level keeps the
+    /// smaller of every pair (one `compare_and_swap`), so ⌈log k⌉ levels and k - 1 comparisons
+    /// in total. Values must lie in [0, bound).
+    pub async fn min_of(&mut self, wires: &[usize], bound: u64) -> Result<Share<F>, BgwError> {
+        let mut values = wires.iter().map(|&w| self.share(w)).collect::<Result<Vec<_>, _>>()?;
+        while values.len() > 1 {
+            let mut winners = Vec::with_capacity(values.len().div_ceil(2));
+            for pair in values.chunks(2) {
+                match *pair {
+                    [a, b] => winners.push(self.compare_and_swap(a, b, bound).await?.0),
+                    [odd] => winners.push(odd),
+                    _ => unreachable!(),
+                }
+            }
+            values = winners;
+        }
+        values.pop().ok_or(BgwError::EmptyInput)
+    }
+
+    /// Shares of the values on `wires` in ascending order, via Batcher's odd-even mergesort.
+    ///
+    /// The network has O(k log^2 k) compare-and-swaps arranged in ⌈log k⌉(⌈log k⌉ + 1) / 2
+    /// layers; swaps within a layer are independent, so the multiplicative depth is that many
+    /// times the depth of one `compare_and_swap` (a comparison plus one multiplication), even
+    /// though this runs them one after another. Values must lie in [0, bound).
+    pub async fn sort(&mut self, wires: &[usize], bound: u64) -> Result<Vec<Share<F>>, BgwError> {
+        let mut values = wires.iter().map(|&w| self.share(w)).collect::<Result<Vec<_>, _>>()?;
+        let k = values.len();
+
+        // the usual power-of-two network, with every comparator that touches an index >= k dropped
+        let mut p = 1;
+        while p < k {
+            let mut step = p;
+            while step >= 1 {
+                for j in (step % p..k.saturating_sub(step)).step_by(2 * step) {
+                    for i in 0..step.min(k - j - step) {
+                        let (lo, hi) = (i + j, i + j + step);
+                        // only compare within the same pair of merged blocks
+                        if lo / (2 * p) == hi / (2 * p) {
+                            let (min, max) = self.compare_and_swap(values[lo], values[hi], bound).await?;
+                            values[lo] = min;
+                            values[hi] = max;
+                        }
+                    }
+                }
+                step /= 2;
+            }
+            p *= 2;
+        }
+        Ok(values)
+    }
+
+    /// A uniformly random permutation of k positions that no coalition of t parties knows.
+    ///
+    /// Parties 0..=t each deal the matrix of a permutation they pick locally, and the matrices
+    /// are multiplied together; at least one dealer is honest, which hides the product. That
+    /// is (t+1) k^2 dealt values and t matrix products of k^3 multiplications each (one
+    /// `mul_shares_batch` per product), so k should stay small.
+    pub async fn random_permutation(&mut self, k: usize) -> Result<SharedPermutation<F>, BgwError> {
+        let mut product: Option<Vec<Vec<Share<F>>>> = None;
+        for dealer in 0..=self.t {
+            let mine = (dealer == self.id).then(|| {
+                let mut order: Vec<usize> = (0..k).collect();
+                order.shuffle(&mut rand::thread_rng());
+                order
+            });
+
+            let mut matrix = Vec::with_capacity(k);
+            for i in 0..k {
+                let mut row = Vec::with_capacity(k);
+                for j in 0..k {
+                    let wire_id = self.scratch_wire();
+                    let entry = mine.as_ref().map(|order| if order[i] == j { F::one() } else { F::zero() });
+                    row.push(self.deal(dealer, wire_id, entry).await?);
+                }
+                matrix.push(row);
+            }
+
+            product = Some(match product {
+                None => matrix,
+                Some(acc) => self.mat_mul(&acc, &matrix).await?,
+            });
+        }
+        Ok(SharedPermutation { matrix: product.unwrap_or_default() })
+    }
+
+    /// the shared product of two shared k×k matrices, all k^3 multiplications in one batch
+    async fn mat_mul(&mut self, a: &[Vec<Share<F>>], b: &[Vec<Share<F>>]) -> Result<Vec<Vec<Share<F>>>, BgwError> {
+        let k = a.len();
+        let mut pairs = Vec::with_capacity(k * k * k);
+        for row in a {
+            for j in 0..k {
+                pairs.extend(row.iter().zip(b).map(|(&x, b_row)| (x, b_row[j])));
+            }
+        }
+        let products = self.mul_shares_batch(&pairs).await?;
+
+        let mut entries = products.chunks(k.max(1))
+            .map(|terms| self.affine(&terms.iter().map(|&s| (s, F::one())).collect::<Vec<_>>(), F::zero()));
+        Ok((0..k).map(|_| entries.by_ref().take(k).collect()).collect())
+    }
+
+    /// The values on `wires` rearranged by `perm`: output i is sum_j perm[i][j] * wire_j, so k^2
+    /// multiplications in one batch. Fails with `LengthMismatch` unless `wires` has `perm.len()`
+    /// entries.
+    pub async fn apply_permutation(&mut self, perm: &SharedPermutation<F>, wires: &[usize]) -> Result<Vec<Share<F>>, BgwError> {
+        if wires.len() != perm.len() {
+            return Err(BgwError::LengthMismatch { expected: perm.len(), got: wires.len() });
+        }
+        let values = wires.iter().map(|&w| self.share(w)).collect::<Result<Vec<_>, _>>()?;
+
+        let pairs: Vec<(Share<F>, Share<F>)> = perm.matrix.iter()
+            .flat_map(|row| row.iter().copied().zip(values.iter().copied()))
+            .collect();
+        let products = self.mul_shares_batch(&pairs).await?;
+
+        Ok(products.chunks(values.len().max(1))
+            .map(|terms| self.affine(&terms.iter().map(|&s| (s, F::one())).collect::<Vec<_>>(), F::zero()))
+            .collect())
+    }
+
+    /// Shared median of the values on `wires`: the middle element after `sort`.
+    ///
+    /// For an even number of values it is the average of the two central elements, computed
+    /// exactly in the field as (a + b) / 2; if a + b is odd that is not an integer but the field
+    /// element (a + b) * 2^-1. Values must lie in [0, bound).
+    pub async fn median(&mut self, wires: &[usize], bound: u64) -> Result<Share<F>, BgwError> {
+        if wires.is_empty() {
+            return Err(BgwError::EmptyInput);
+        }
+        let sorted = self.sort(wires, bound).await?;
+        let mid = sorted.len() / 2;
+        if sorted.len() % 2 == 1 {
+            return Ok(sorted[mid]);
+        }
+        let half = F::from(2u64).inverse().expect("2 is invertible in an odd prime field");
+        Ok(self.affine(&[(sorted[mid - 1], half), (sorted[mid], half)], F::zero()))
+    }
+}
+
+fn field_from_i64<F: PrimeField>(v: i64) -> F {
+    if v < 0 { -F::from(v.unsigned_abs()) } else { F::from(v as u64) }
+}
+
+/// coefficients of the polynomial mapping every d in (-bound, bound) to `indicator(d)` as 0/1,
+/// interpolated once per field, `name` and `bound`
+fn indicator_polynomial<F: PrimeField>(name: &'static str, bound: u64, indicator: fn(i64) -> bool) -> Result<Arc<Vec<F>>, BgwError> {
+    type Cache = Mutex<HashMap<(TypeId, &'static str, u64), Arc<dyn Any + Send + Sync>>>;
+    static CACHE: OnceLock<Cache> = OnceLock::new();
+
+    let signed_bound = i64::try_from(bound).ok().filter(|&b| b >= 1).ok_or(BgwError::InvalidBound { bound })?;
+    let mut cache = CACHE.get_or_init(Default::default).lock().unwrap();
+    let coeffs = cache.entry((TypeId::of::<F>(), name, bound)).or_insert_with(|| {
+        let points: Vec<(F, F)> = (1 - signed_bound..signed_bound)
+            .map(|d| (field_from_i64(d), if indicator(d) { F::one() } else { F::zero() }))
+            .collect();
+        Arc::new(interpolate(&points))
+    });
+    Ok(coeffs.clone().downcast().expect("cached under this field's TypeId"))
+}
+
+/// monomial coefficients of the unique polynomial of degree < points.len() through `points`
+fn interpolate<F: PrimeField>(points: &[(F, F)]) -> Vec<F> {
+    let mut coeffs = vec![F::zero(); points.len()];
+
+    for (i, &(xi, yi)) in points.iter().enumerate() {
+        if yi.is_zero() {
+            continue;
+        }
+
+        // build ℓ_i(X) = \prod_{j != i} (X - x_j) / (x_i - x_j), one factor at a time
+        let mut basis = vec![F::one()];
+        let mut den = F::one();
+        for (j, &(xj, _)) in points.iter().enumerate() {
+            if i != j {
+                let mut next = vec![F::zero(); basis.len() + 1];
+                for (k, &c) in basis.iter().enumerate() {
+                    next[k + 1] += c;
+                    next[k] -= c * xj;
+                }
+                basis = next;
+                den *= xi - xj;
+            }
+        }
+
+        let scale = yi * den.inverse().expect("Duplicate x values in interpolation points");
+        for (c, b) in coeffs.iter_mut().zip(basis) {
+            *c += b * scale;
+        }
+    }
+    coeffs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_ff::{One, Zero};
+    use crate::party::tests::network;
+    use crate::sharing::{shamir_reconstruct, shamir_share};
+    use std::future::Future;
+    use std::pin::Pin;
+
+    type OpFuture = Pin<Box<dyn Future<Output = (Party<Fr>, Vec<Share<Fr>>)> + Send>>;
+
+    /// deal `values` to n = 3 parties on wires 0..k, run `op` on every party concurrently,
+    /// and reconstruct each of the shares it returns
+    async fn run_op(values: &[u64], op: fn(Party<Fr>, Vec<usize>) -> OpFuture) -> Vec<Fr> {
+        let (n, t) = (3, 1);
+        let dealt: Vec<Vec<Share<Fr>>> = values.iter().map(|&v| shamir_share(Fr::from(v), t, n)).collect();
+        let wires: Vec<usize> = (0..values.len()).collect();
+
+        let handles: Vec<_> = network(n, t).into_iter().map(|mut party| {
+            for (wire_id, shares) in dealt.iter().enumerate() {
+                party.shares.insert(wire_id, shares[party.id]);
+            }
+            tokio::spawn(op(party, wires.clone()))
+        }).collect();
+
+        let mut results = Vec::new();
+        for h in handles {
+            results.push(h.await.unwrap());
+        }
+        let outputs: Vec<Vec<Share<Fr>>> = results.into_iter().map(|(_, out)| out).collect();
+        (0..outputs[0].len())
+            .map(|i| shamir_reconstruct(&outputs.iter().map(|o| o[i]).collect::<Vec<_>>()))
+            .collect()
+    }
+
+    #[test]
+    fn test_interpolate_recovers_coefficients() {
+        // 3 - 2x + x^2
+        let f = |x: Fr| Fr::from(3u64) - Fr::from(2u64) * x + x * x;
+        let points: Vec<(Fr, Fr)> = (0..3u64).map(|x| (Fr::from(x), f(Fr::from(x)))).collect();
+        assert_eq!(interpolate(&points), vec![Fr::from(3u64), -Fr::from(2u64), Fr::one()]);
+    }
+
+    #[tokio::test]
+    async fn test_less_than() {
+        let result = run_op(&[3, 9, 9, 0, 15], |mut p, w| Box::pin(async move {
+            let s: Vec<Share<Fr>> = w.iter().map(|&w| p.shares[&w]).collect();
+            let out = vec![
+                p.less_than(s[0], s[1], 16).await.unwrap(), // 3 < 9
+                p.less_than(s[1], s[0], 16).await.unwrap(), // 9 < 3
+                p.less_than(s[1], s[2], 16).await.unwrap(), // 9 < 9
+                p.less_than(s[3], s[4], 16).await.unwrap(), // 0 < 15
+            ];
+            (p, out)
+        })).await;
+        assert_eq!(result, [1u64, 0, 0, 1].map(Fr::from));
+    }
+
+    #[tokio::test]
+    async fn test_comparison_takes_log_rounds() {
+        run_op(&[3, 9], |mut p, w| Box::pin(async move {
+            let s: Vec<Share<Fr>> = w.iter().map(|&w| p.shares[&w]).collect();
+            let before = p.metrics().mul_rounds;
+            p.less_than(s[0], s[1], 16).await.unwrap();
+            // x^1..x^30 by doubling: 2, 4, 8, 16, 30
+            assert_eq!(p.metrics().mul_rounds - before, 5);
+            (p, Vec::new())
+        })).await;
+    }
+
+    #[tokio::test]
+    async fn test_comparison_rejects_an_out_of_range_bound() {
+        run_op(&[3, 9], |mut p, w| Box::pin(async move {
+            let s: Vec<Share<Fr>> = w.iter().map(|&w| p.shares[&w]).collect();
+            assert_eq!(p.less_than(s[0], s[1], 0).await, Err(BgwError::InvalidBound { bound: 0 }));
+            assert_eq!(p.is_zero(s[0], u64::MAX).await, Err(BgwError::InvalidBound { bound: u64::MAX }));
+            (p, Vec::new())
+        })).await;
+    }
+
+    #[tokio::test]
+    async fn test_max_with_index() {
+        let result = run_op(&[3, 9, 2, 7], |mut p, w| Box::pin(async move {
+            let (max, index) = p.max_with_index(&w, 16).await.unwrap();
+            (p, vec![max, index])
+        })).await;
+        assert_eq!(result, vec![Fr::from(9u64), Fr::from(1u64)]);
+    }
+
+    #[tokio::test]
+    async fn test_min_of() {
+        let min = |mut p: Party<Fr>, w: Vec<usize>| -> OpFuture {
+            Box::pin(async move {
+                let out = p.min_of(&w, 16).await.unwrap();
+                (p, vec![out])
+            })
+        };
+        assert_eq!(run_op(&[5, 2, 8, 1], min).await, vec![Fr::from(1u64)]);
+        assert_eq!(run_op(&[6, 3, 9], min).await, vec![Fr::from(3u64)]);
+    }
+
+    #[tokio::test]
+    async fn test_sort() {
+        let sort = |mut p: Party<Fr>, w: Vec<usize>| -> OpFuture {
+            Box::pin(async move {
+                let sorted = p.sort(&w, 16).await.unwrap();
+                (p, sorted)
+            })
+        };
+        assert_eq!(run_op(&[5, 2, 8, 1], sort).await, [1u64, 2, 5, 8].map(Fr::from));
+        assert_eq!(run_op(&[7, 0, 4], sort).await, [0u64, 4, 7].map(Fr::from));
+    }
+
+    #[tokio::test]
+    async fn test_median() {
+        let median = |mut p: Party<Fr>, w: Vec<usize>| -> OpFuture {
+            Box::pin(async move {
+                let out = p.median(&w, 16).await.unwrap();
+                (p, vec![out])
+            })
+        };
+        assert_eq!(run_op(&[7, 1, 5, 3, 9], median).await, vec![Fr::from(5u64)]);
+        // (4 + 8) / 2
+        assert_eq!(run_op(&[8, 2, 4, 10], median).await, vec![Fr::from(6u64)]);
+    }
+
+    #[tokio::test]
+    async fn test_random_permutation_rearranges_the_inputs() {
+        let permute = |mut p: Party<Fr>, w: Vec<usize>| -> OpFuture {
+            Box::pin(async move {
+                let perm = p.random_permutation(w.len()).await.unwrap();
+                let mut out = p.apply_permutation(&perm, &w).await.unwrap();
+                // the same permutation again, on the reversed vector
+                let reversed: Vec<usize> = w.iter().rev().copied().collect();
+                out.extend(p.apply_permutation(&perm, &reversed).await.unwrap());
+                let short = p.apply_permutation(&perm, &w[1..]).await;
+                assert_eq!(short, Err(BgwError::LengthMismatch { expected: 4, got: 3 }));
+                (p, out)
+            })
+        };
+        let result = run_op(&[1, 2, 3, 4], permute).await;
+        let (first, second) = result.split_at(4);
+
+        let mut sorted = first.to_vec();
+        sorted.sort();
+        assert_eq!(sorted, [1u64, 2, 3, 4].map(Fr::from));
+        // position i took input j the first time and input 3 - j the second time
+        for (a, b) in first.iter().zip(second) {
+            assert_eq!(*a + *b, Fr::from(5u64));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_histogram() {
+        let result = run_op(&[1, 2, 1, 3], |mut p, w| Box::pin(async move {
+            let before = p.metrics().mul_rounds;
+            let counts = p.histogram(&w, &[1u64, 2, 3].map(Fr::from), 4).await.unwrap();
+            // every bin shares the rounds of one is_zero: x^1..x^6 by doubling is 2, 4, 6
+            assert_eq!(p.metrics().mul_rounds - before, 3);
+            (p, counts)
+        })).await;
+        assert_eq!(result, [2u64, 1, 1].map(Fr::from));
+    }
+
+    #[tokio::test]
+    async fn test_ripple_carry_add() {
+        // 5 = 0101 and 6 = 0110, least significant bit first
+        let add = |mut p: Party<Fr>, w: Vec<usize>| -> OpFuture {
+            Box::pin(async move {
+                let bits = p.ripple_carry_add(&w[..4], &w[4..]).await.unwrap();
+                (p, bits)
+            })
+        };
+        let result = run_op(&[1, 0, 1, 0, 0, 1, 1, 0], add).await;
+        // 11 = 01011
+        assert_eq!(result, [1u64, 1, 0, 1, 0].map(Fr::from));
+
+        // 15 + 15 = 30 carries out of the top bit
+        let result = run_op(&[1, 1, 1, 1, 1, 1, 1, 1], add).await;
+        assert_eq!(result, [0u64, 1, 1, 1, 1].map(Fr::from));
+
+        let uneven = |mut p: Party<Fr>, w: Vec<usize>| -> OpFuture {
+            Box::pin(async move {
+                let result = p.ripple_carry_add(&w[..2], &w[2..]).await;
+                assert_eq!(result, Err(BgwError::LengthMismatch { expected: 2, got: 1 }));
+                (p, Vec::new())
+            })
+        };
+        run_op(&[1, 0, 1], uneven).await;
+    }
+
+    #[tokio::test]
+    async fn test_assert_bit() {
+        let check = |mut p: Party<Fr>, w: Vec<usize>| -> OpFuture {
+            Box::pin(async move {
+                assert_eq!(p.assert_bit(w[0]).await, Ok(()));
+                assert_eq!(p.assert_bit(w[1]).await, Ok(()));
+                assert_eq!(p.assert_bit(w[2]).await, Err(BgwError::AssertionFailed { wire_id: w[2] }));
+                (p, Vec::new())
+            })
+        };
+        run_op(&[0, 1, 2], check).await;
+    }
+
+    #[tokio::test]
+    async fn test_bit_xor() {
+        let xor = |mut p: Party<Fr>, w: Vec<usize>| -> OpFuture {
+            Box::pin(async move {
+                let s: Vec<Share<Fr>> = w.iter().map(|&w| p.shares[&w]).collect();
+                let mut out = Vec::new();
+                for (a, b) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+                    out.push(p.bit_xor(s[a], s[b]).await.unwrap());
+                }
+                (p, out)
+            })
+        };
+        assert_eq!(run_op(&[0, 1], xor).await, [0u64, 1, 1, 0].map(Fr::from));
+    }
+
+    #[tokio::test]
+    async fn test_bit_or_and_many() {
+        let or = |mut p: Party<Fr>, w: Vec<usize>| -> OpFuture {
+            Box::pin(async move {
+                let out = p.bit_or_many(&w).await.unwrap();
+                (p, vec![out])
+            })
+        };
+        let and = |mut p: Party<Fr>, w: Vec<usize>| -> OpFuture {
+            Box::pin(async move {
+                let out = p.bit_and_many(&w).await.unwrap();
+                (p, vec![out])
+            })
+        };
+        assert_eq!(run_op(&[0, 0, 1, 0], or).await, vec![Fr::one()]);
+        assert_eq!(run_op(&[0, 0, 0, 0], or).await, vec![Fr::zero()]);
+        assert_eq!(run_op(&[1, 1, 1], and).await, vec![Fr::one()]);
+        assert_eq!(run_op(&[1, 0, 1], and).await, vec![Fr::zero()]);
+    }
+}
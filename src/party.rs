@@ -1,229 +1,2133 @@
-use ark_bn254::Fr;
-use ark_ff::PrimeField;
-use ark_ff::Field;
-use ark_ff::BigInteger;
-use tokio::time::{timeout, Duration};
+use tokio::time::{timeout, timeout_at, Duration, Instant};
 
-use std::collections::HashMap;
-use tokio::sync::{mpsc, Barrier};
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet, VecDeque};
+use tokio::sync::mpsc;
+use tracing::{debug, instrument, trace, warn};
 
-use crate::sharing::{shamir_share, shamir_reconstruct, Share};
-use crate::circuit::{Circuit, GateType};
-use crate::message::Message;
+use crate::sharing::{
+    lagrange_coefficients, shamir_reconstruct_checked, shamir_share_at, shamir_share_with_commitments, verify_share,
+    BeaverTriple, LagrangeWeights, ProtocolField, ReconstructError, Share,
+};
+use crate::circuit::{Circuit, CircuitError, GateType};
+use crate::error::BgwError;
+use crate::message::{Envelope, Message, MAX_REASON_LEN};
+use crate::transport::{ChannelTransport, Transport};
 
-/// A party participating in the BGW protocol
-pub struct Party {
-    pub id: usize,
+/// how often (in gates) `evaluate_circuit` reports progress, besides after every multiplication
+const PROGRESS_INTERVAL: usize = 64;
+
+/// how long a party waits by default for the next message it needs during evaluation or
+/// output before giving up on the parties that haven't sent it
+pub const DEFAULT_RECV_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Who learns an output wire in `output_phase`
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum OutputPolicy {
+    #[default]
+    Owner,  // an Output gate with an owner is revealed to that party only, the rest to everyone
+    Public, // every output wire is revealed to everyone, owners are ignored
+}
+
+/// How `output_phase` turns the collected shares into a value
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Reconstructor {
+    #[default]
+    Interpolate, // interpolate from the first t+1 shares that arrive
+    Checked,     // wait for every party (up to the receive timeout) and check the shares agree on degree t
+}
+
+/// Everything needed to set up a party, besides its id and transport
+#[derive(Clone, Debug)]
+pub struct PartyConfig {
     pub n: usize,
     pub t: usize,
-    pub shares: HashMap<usize, Share>, // wire_id → Share
-    pub tx: HashMap<usize, mpsc::Sender<Message>>, // recipient → Sender<Message>
-    pub rx: mpsc::Receiver<Message>, // centralized inbox
-    pub barrier: Arc<Barrier>, // barrier for synchronization
+    pub run_id: u64,
+    pub input_timeout: Duration, // how long to wait for foreign inputs before using their defaults
+    pub recv_timeout: Duration,  // how long to wait for any other message before giving up
+    pub output_policy: OutputPolicy,
+    pub reconstructor: Reconstructor,
+}
+
+impl PartyConfig {
+    /// n parties tolerating t corruptions, everything else at its default
+    pub fn new(n: usize, t: usize) -> Self {
+        PartyConfig {
+            n,
+            t,
+            run_id: 0,
+            input_timeout: Duration::from_secs(10),
+            recv_timeout: DEFAULT_RECV_TIMEOUT,
+            output_policy: OutputPolicy::default(),
+            reconstructor: Reconstructor::default(),
+        }
+    }
+}
+
+/// A progress update emitted while evaluating a circuit
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Progress {
+    pub gates_done: usize,
+    pub gates_total: usize,
+    pub current_round: usize, // communication rounds completed so far during evaluation
+}
+
+/// Communication counters, accumulated over every phase a party runs
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Metrics {
+    pub messages_sent: usize,
+    pub messages_received: usize, // everything taken off the transport, including other runs' messages
+    pub bytes_sent: usize, // encoded size of the sent messages (see `Envelope::to_bytes`)
+    pub mul_gates_evaluated: usize,
+    pub mul_rounds: usize, // communication rounds spent in multiplications
 }
 
-impl Party {
+/// A party participating in the BGW protocol
+///
+/// build one with `Party::with_config`, and adjust it with the `with_*` methods
+pub struct Party<F: ProtocolField, T: Transport<F> = ChannelTransport<F>> {
+    pub(crate) id: usize,
+    pub(crate) run_id: u64, // identifies this execution; messages tagged with any other run are dropped
+    pub(crate) n: usize,
+    pub(crate) t: usize,
+    pub(crate) x_coords: Vec<F>, // party id → evaluation point of that party's shares
+    pub(crate) shares: HashMap<usize, Share<F>>, // wire_id → Share
+    pub(crate) transport: T, // connection to every party (including ourselves)
+    pub(crate) pending: VecDeque<Message<F>>, // received but not yet consumed (e.g. a message for a later phase)
+    pub(crate) input_timeout: Duration, // how long to wait for foreign inputs before using their defaults
+    pub(crate) recv_timeout: Duration, // how long to wait for any other message before giving up
+    pub(crate) output_policy: OutputPolicy,
+    pub(crate) reconstructor: Reconstructor,
+    pub(crate) progress: Option<mpsc::Sender<Progress>>, // optional progress reporting during evaluation
+    pub(crate) triples: VecDeque<BeaverTriple<F>>, // preprocessed triples, consumed in order by Mul gates
+    pub(crate) next_scratch_wire: usize, // counter for intermediate wires created by secure operations (see ops)
+    pub(crate) lagrange_cache: HashMap<Vec<F>, LagrangeWeights<F>>, // sorted x-set → its weights, reused across reconstructions
+    pub(crate) absent: HashSet<usize>, // parties that missed a multiplication deadline; later ones don't wait for them
+    pub(crate) metrics: Metrics,
+}
+
+impl<F: ProtocolField, T: Transport<F>> Party<F, T> {
+    /// party `id` of `config.n`, holding its shares at x = id + 1
+    pub fn with_config(id: usize, config: PartyConfig, transport: T) -> Self {
+        Party {
+            id,
+            run_id: config.run_id,
+            n: config.n,
+            t: config.t,
+            x_coords: (1..=config.n).map(|i| F::from(i as u64)).collect(),
+            shares: HashMap::new(),
+            transport,
+            pending: VecDeque::new(),
+            input_timeout: config.input_timeout,
+            recv_timeout: config.recv_timeout,
+            output_policy: config.output_policy,
+            reconstructor: config.reconstructor,
+            progress: None,
+            triples: VecDeque::new(),
+            next_scratch_wire: 0,
+            lagrange_cache: HashMap::new(),
+            absent: HashSet::new(),
+            metrics: Metrics::default(),
+        }
+    }
+
+    /// use `x_coords` (party id → evaluation point) instead of x = id + 1. every party must be
+    /// given the same points; fails with `InvalidXCoords` unless there is one per party, all
+    /// distinct (or nothing can be interpolated) and non-zero (a share at 0 is the secret itself)
+    pub fn with_x_coords(mut self, x_coords: Vec<F>) -> Result<Self, BgwError> {
+        let distinct: HashSet<F> = x_coords.iter().copied().collect();
+        if x_coords.len() != self.n || distinct.len() != self.n || distinct.contains(&F::zero()) {
+            return Err(BgwError::InvalidXCoords);
+        }
+        self.x_coords = x_coords;
+        Ok(self)
+    }
+
+    /// preprocessed triples for `eval_mul_beaver` (see `generate_beaver_triples`), used in order
+    pub fn with_triples(mut self, triples: impl IntoIterator<Item = BeaverTriple<F>>) -> Self {
+        self.triples.extend(triples);
+        self
+    }
+
+    /// report progress on `progress` while evaluating a circuit
+    pub fn with_progress(mut self, progress: mpsc::Sender<Progress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn t(&self) -> usize {
+        self.t
+    }
+
+    pub fn run_id(&self) -> u64 {
+        self.run_id
+    }
+
+    /// every party's evaluation point, by id
+    pub fn x_coords(&self) -> &[F] {
+        &self.x_coords
+    }
+
+    /// the evaluation point assigned to this party
+    pub fn x(&self) -> F {
+        self.x_coords[self.id]
+    }
+
+    /// messages, bytes and multiplications so far
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// this party's share of `wire_id`
+    pub fn share(&self, wire_id: usize) -> Result<Share<F>, BgwError> {
+        self.shares.get(&wire_id).copied().ok_or(BgwError::MissingShare { wire_id })
+    }
+
+    /// hold `share` as this party's share of `wire_id`, e.g. one dealt outside `input_phase`
+    pub fn set_share(&mut self, wire_id: usize, share: Share<F>) {
+        self.shares.insert(wire_id, share);
+    }
+
     /// Input Phase: share your inputs and receive others' inputs
-    pub async fn input_phase(&mut self, circuit: &Circuit, inputs: &HashMap<usize, Fr>) {
+    ///
+    /// every dealer sends all of its shares before it starts receiving, so all the owners deal
+    /// at once and the phase takes one round however many of them there are. follow it with
+    /// `input_barrier` to start evaluating only once every party has its inputs.
+    ///
+    /// every input is dealt with the field's commitments (Pedersen for BN254, see `fields`), and a
+    /// received share that doesn't match its dealer's commitments aborts with `InvalidShare`.
+    /// the commitments are sent point-to-point, so a dealer could still show different parties
+    /// different commitments.
+    #[instrument(skip_all, fields(party = self.id, run = self.run_id), err(level = "warn"))]
+    pub async fn input_phase(&mut self, circuit: &Circuit<F>, inputs: &HashMap<usize, F>) -> Result<(), BgwError> {
         let input_wires = circuit.input_wires_by_owner(self.id);
 
         // Share owned inputs
         for &wire_id in &input_wires {
-            let secret = *inputs.get(&wire_id).expect("Missing input value!");
-            let shares = shamir_share(secret, self.t, self.n);
+            let secret = *inputs.get(&wire_id).ok_or(BgwError::MissingInput { wire_id })?;
+            let (shares, blinding, commitments) = shamir_share_with_commitments(secret, self.t, &self.x_coords);
+            self.broadcast(Message::Commitments(wire_id, commitments)).await?;
 
-            for (pid, &share) in shares.iter().enumerate() {
-                let msg = Message::InputShare(wire_id, share);
+            for (pid, (&share, &blinding)) in shares.iter().zip(&blinding).enumerate() {
+                let msg = Message::InputShare(wire_id, share, blinding);
                 if pid == self.id {
                     self.shares.insert(wire_id, share);
                 } else {
-                    let tx = self.tx.get_mut(&pid).unwrap();
-                    tx.send(msg).await.expect("Failed to send input share");
+                    self.send(pid, msg).await?;
                 }
             }
         }
 
         // Receive inputs from other parties
-        let expected = circuit.gates.iter()
+        let mut missing: Vec<usize> = circuit.gates.iter()
             .filter(|g| matches!(g.gate_type, GateType::Input) && g.owner != Some(self.id))
-            .count();
+            .map(|g| g.id)
+            .collect();
+        let mut dealt: HashMap<usize, (Share<F>, F)> = HashMap::new(); // wire → (share, blinding)
+        let mut commitments: HashMap<usize, Vec<F::Commitment>> = HashMap::new();
 
-        let mut received = 0;
-        while received < expected {
-            if let Some(Message::InputShare(wire_id, share)) = self.rx.recv().await {
-                self.shares.insert(wire_id, share);
-                received += 1;
+        // inputs with a default are only waited for until the deadline
+        let deadline = Instant::now() + self.input_timeout;
+
+        while !missing.is_empty() {
+            let has_default = missing.iter().any(|&w| circuit.gates[w].default.is_some());
+            let received = self.recv_matching(|msg| match msg {
+                Message::InputShare(wire_id, ..) | Message::Commitments(wire_id, _) if missing.contains(wire_id) => Some(msg.clone()),
+                _ => None,
+            });
+            let received = if has_default {
+                timeout_at(deadline, received).await
+            } else {
+                Ok(received.await)
+            };
+
+            let wire_id = match received {
+                Ok(Ok(Message::InputShare(wire_id, share, blinding))) => {
+                    dealt.insert(wire_id, (share, blinding));
+                    wire_id
+                }
+                Ok(Ok(Message::Commitments(wire_id, points))) => {
+                    commitments.insert(wire_id, points);
+                    wire_id
+                }
+                Ok(Ok(_)) => unreachable!("only input shares and commitments are accepted"),
+                Ok(Err(e)) => return Err(e),
+                Err(_) => {
+                    warn!(wires = ?missing, "input deadline passed, falling back to defaults");
+                    // the default is a public constant, i.e. the degree-0 sharing f(x) = default
+                    let x = self.x();
+                    for &wire_id in &missing {
+                        if let Some(default) = circuit.gates[wire_id].default {
+                            self.shares.insert(wire_id, Share { x, value: default });
+                        }
+                    }
+                    missing.retain(|&w| circuit.gates[w].default.is_none());
+                    continue;
+                }
+            };
+
+            // accept the share once both it and the dealer's commitments are in
+            if let (Some((share, blinding)), Some(points)) = (dealt.get(&wire_id), commitments.get(&wire_id)) {
+                let valid = share.x == self.x() && points.len() == self.t + 1 && verify_share(share, *blinding, points);
+                if !valid {
+                    let from = circuit.gates[wire_id].owner.unwrap_or(usize::MAX);
+                    return Err(BgwError::InvalidShare { from });
+                }
+                self.shares.insert(wire_id, *share);
+                missing.retain(|&w| w != wire_id);
             }
         }
+        Ok(())
+    }
+
+    /// Wait until every party has finished its input phase: announce it with `Ready`, then
+    /// wait (up to the receive timeout) for everyone else's. one extra round, after which no
+    /// party can still be waiting on an input share when evaluation begins
+    #[instrument(skip_all, fields(party = self.id, run = self.run_id), err(level = "warn"))]
+    pub async fn input_barrier(&mut self) -> Result<(), BgwError> {
+        self.broadcast(Message::Ready(self.id)).await?;
+
+        let recv_timeout = self.recv_timeout;
+        let mut missing: Vec<usize> = (0..self.n).filter(|&pid| pid != self.id).collect();
+        while !missing.is_empty() {
+            let received = self.recv_matching(|msg| match *msg {
+                Message::Ready(from) if missing.contains(&from) => Some(from),
+                _ => None,
+            });
+            match timeout(recv_timeout, received).await {
+                Ok(Ok(from)) => missing.retain(|&pid| pid != from),
+                Ok(Err(e)) => return Err(e),
+                Err(_) => return Err(BgwError::BarrierTimeout { missing }),
+            }
+        }
+        Ok(())
+    }
+
+    /// check that every input wire of `circuit`, ours and everyone else's, has a share
+    ///
+    /// meant to run between `input_phase` and `evaluate_circuit`, so a lost input is reported
+    /// up front (all missing wires at once) instead of at the first gate that reads it
+    pub fn verify_all_inputs_present(&self, circuit: &Circuit<F>) -> Result<(), BgwError> {
+        let wire_ids: Vec<usize> = circuit.gates.iter()
+            .filter(|g| matches!(g.gate_type, GateType::Input) && !self.shares.contains_key(&g.id))
+            .map(|g| g.id)
+            .collect();
+        if wire_ids.is_empty() {
+            Ok(())
+        } else {
+            Err(BgwError::MissingShares { wire_ids })
+        }
     }
 
     /// Evaluate circuit using received and computed shares
-    pub async fn evaluate_circuit(&mut self, circuit: &Circuit) {
-        for gate_id in circuit.topological_order() {
-            let gate = &circuit.gates[gate_id];
-            match gate.gate_type {
-                GateType::Input => {
-                    assert!(self.shares.contains_key(&gate.id), "Missing input share for wire {}", gate.id);
-                }
-                GateType::Add => {
-                    self.eval_add(gate.id, gate.left.unwrap(), gate.right.unwrap());
-                }
-                GateType::ConstMul(c) => {
-                    self.eval_const_mul(gate.id, gate.left.unwrap(), c);
+    ///
+    /// gates are visited one layer (see `Circuit::layers`) at a time; all the Mul gates of a
+    /// layer are independent, so their reshares go out together in one `eval_mul_batch` and
+    /// the whole layer costs one round instead of one per gate
+    #[instrument(skip_all, fields(party = self.id, run = self.run_id), err(level = "warn"))]
+    pub async fn evaluate_circuit(&mut self, circuit: &Circuit<F>) -> Result<(), BgwError> {
+        let layers = circuit.try_layers()?;
+        let gates_total = circuit.gates.len();
+        let mut gates_done = 0;
+        let mut round = 0;
+
+        for layer in &layers {
+            let mut muls = Vec::new();
+            for &gate_id in layer {
+                let gate = &circuit.gates[gate_id];
+                let operand = |wire: Option<usize>| wire.ok_or(CircuitError::MissingOperand { gate_id });
+                match gate.gate_type {
+                    GateType::Input => {
+                        self.share(gate.id)?;
+                    }
+                    GateType::Add => {
+                        self.eval_add(gate.id, operand(gate.left)?, operand(gate.right)?)?;
+                    }
+                    GateType::ConstMul(c) => {
+                        self.eval_const_mul(gate.id, operand(gate.left)?, c)?;
+                    }
+                    GateType::AddConst(c) => {
+                        self.eval_add_const(gate.id, operand(gate.left)?, c)?;
+                    }
+                    GateType::Mul if self.triples.is_empty() => {
+                        muls.push((gate.id, operand(gate.left)?, operand(gate.right)?));
+                        continue; // evaluated with the rest of the layer below
+                    }
+                    GateType::Mul => {
+                        self.eval_mul_beaver(gate.id, operand(gate.left)?, operand(gate.right)?).await?;
+                        round += 1; // opening of the masked operands
+                    }
+                    GateType::ToAdditive => {
+                        self.eval_to_additive(gate.id, operand(gate.left)?)?;
+                    }
+                    GateType::ToShamir => {
+                        self.eval_to_shamir(gate.id, operand(gate.left)?).await?;
+                        round += 1;
+                    }
+                    GateType::Output => {
+                        let share = self.share(operand(gate.left)?)?;
+                        self.shares.insert(gate.id, share);
+                    }
                 }
-                GateType::Mul => {
-                    let out = gate.id;
-                    let left = gate.left.unwrap();
-                    let right = gate.right.unwrap();
-                    self.eval_mul(out, left, right).await;
+
+                gates_done += 1;
+                if matches!(gate.gate_type, GateType::Mul) || gates_done % PROGRESS_INTERVAL == 0 || gates_done == gates_total {
+                    self.report_progress(Progress { gates_done, gates_total, current_round: round }).await;
                 }
-                GateType::Output => {
-                    let input_wire = gate.left.unwrap();
-                    let share = self.shares[&input_wire];
-                    self.shares.insert(gate.id, share);
+            }
+
+            if !muls.is_empty() {
+                // one resharing for the whole layer, plus one degree check if there was room for it
+                let rounds_before = self.metrics.mul_rounds;
+                self.eval_mul_batch(&muls).await?;
+                round += self.metrics.mul_rounds - rounds_before;
+                gates_done += muls.len();
+                debug!(round, muls = muls.len(), "mul layer done");
+                self.report_progress(Progress { gates_done, gates_total, current_round: round }).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn report_progress(&self, progress: Progress) {
+        if let Some(tx) = &self.progress {
+            // a dropped receiver just means nobody is listening anymore
+            let _ = tx.send(progress).await;
+        }
+    }
+
+    /// Send `msg` to party `to`
+    ///
+    /// While the send waits on a full inbox at `to`, this party keeps draining its own inbox
+    /// into `pending`: otherwise two parties sending each other more than an inbox holds would
+    /// both block forever.
+    async fn send(&mut self, to: usize, msg: Message<F>) -> Result<(), BgwError> {
+        let envelope = Envelope { run_id: self.run_id, msg };
+        self.metrics.messages_sent += 1;
+        self.metrics.bytes_sent += envelope.encoded_len();
+
+        let send = self.transport.send(to, envelope);
+        tokio::pin!(send);
+        loop {
+            tokio::select! {
+                biased;
+                result = &mut send => return result.map_err(|_| BgwError::SendFailed { to }),
+                Some(Envelope { run_id, msg }) = self.transport.recv() => {
+                    self.metrics.messages_received += 1;
+                    if run_id == self.run_id {
+                        self.pending.push_back(msg);
+                    }
                 }
             }
         }
     }
 
+    /// Send `msg` to every other party
+    async fn broadcast(&mut self, msg: Message<F>) -> Result<(), BgwError> {
+        for pid in 0..self.n {
+            if pid != self.id {
+                self.send(pid, msg.clone()).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Receive the first message accepted by `accept`, buffering any others for later.
+    ///
+    /// Parties run at different speeds, so a message for a later gate or phase can arrive
+    /// before the one we are waiting for; it is kept in `pending` instead of being dropped.
+    /// Messages from a different run are discarded, and an abort from any party fails the receive.
+    async fn recv_matching<R>(&mut self, mut accept: impl FnMut(&Message<F>) -> Option<R>) -> Result<R, BgwError> {
+        for i in 0..self.pending.len() {
+            if let Message::Abort(reason) = &self.pending[i] {
+                return Err(BgwError::ProtocolAborted(reason.clone()));
+            }
+            if let Some(value) = accept(&self.pending[i]) {
+                self.pending.remove(i);
+                return Ok(value);
+            }
+        }
+
+        while let Some(Envelope { run_id, msg }) = self.transport.recv().await {
+            self.metrics.messages_received += 1;
+            if run_id != self.run_id {
+                continue;
+            }
+            if let Message::Abort(reason) = &msg {
+                let error = BgwError::ProtocolAborted(reason.clone());
+                self.pending.push_back(msg); // every later receive fails the same way
+                return Err(error);
+            }
+            if let Some(value) = accept(&msg) {
+                return Ok(value);
+            }
+            self.pending.push_back(msg);
+        }
+        Err(BgwError::ChannelClosed)
+    }
+
+    /// Tell every other party to stop, returning the error this party stops with.
+    ///
+    /// Once the abort arrives, every receive on the other parties fails with the same
+    /// `ProtocolAborted`, so nobody carries on after an inconsistency was detected.
+    pub async fn abort(&mut self, mut reason: String) -> BgwError {
+        // receivers reject longer reasons; cut at a char boundary so it stays valid UTF-8
+        if reason.len() > MAX_REASON_LEN {
+            let end = (0..=MAX_REASON_LEN).rev().find(|&i| reason.is_char_boundary(i)).unwrap_or(0);
+            reason.truncate(end);
+        }
+        warn!(%reason, "aborting the run");
+        for pid in 0..self.n {
+            if pid != self.id {
+                // best effort: a party that is already gone has nothing left to abort
+                let _ = self.send(pid, Message::Abort(reason.clone())).await;
+            }
+        }
+        BgwError::ProtocolAborted(reason)
+    }
+
     /// Output Phase: exchange output shares and reconstruct result
-    pub async fn output_phase(&mut self, output_wires: &[usize]) -> HashMap<usize, Fr> {
-        let mut collected: HashMap<usize, Vec<Share>> = HashMap::new();
+    ///
+    /// under `OutputPolicy::Owner`, an Output gate with an owner is only revealed to that party:
+    /// everyone sends their share to the owner alone, and the wire is absent from every other
+    /// party's result. wires without an owner (or that aren't Output gates of `circuit`) are
+    /// opened to everyone.
+    #[instrument(skip_all, fields(party = self.id, run = self.run_id), err(level = "warn"))]
+    pub async fn output_phase(&mut self, circuit: &Circuit<F>, output_wires: &[usize]) -> Result<HashMap<usize, F>, BgwError> {
+        let mut collected: HashMap<usize, Vec<Share<F>>> = HashMap::new();
 
         for &wire_id in output_wires {
-            let share = self.shares[&wire_id];
-            for (&pid, tx) in &mut self.tx {
-                if pid != self.id {
-                    tx.send(Message::OutputShare(wire_id, share)).await.expect("Failed to send output share");
+            let share = self.share(wire_id)?;
+            let recipient = circuit.gates.get(wire_id)
+                .filter(|g| matches!(g.gate_type, GateType::Output) && self.output_policy == OutputPolicy::Owner)
+                .and_then(|g| g.owner);
+            match recipient {
+                None => {
+                    // a party with t+1 shares of every output is done and may have hung up
+                    // while we still send: it doesn't need ours, so a failed send isn't fatal
+                    for pid in 0..self.n {
+                        if pid != self.id {
+                            let _ = self.send(pid, Message::OutputShare(wire_id, share)).await;
+                        }
+                    }
+                }
+                Some(owner) if owner != self.id => {
+                    self.send(owner, Message::OutputShare(wire_id, share)).await?;
+                    continue;
                 }
+                Some(_) => {}
             }
             collected.entry(wire_id).or_default().push(share);
         }
 
-        while collected.values().any(|v| v.len() < self.t + 1) {
-            if let Some(Message::OutputShare(wire_id, share)) = self.rx.recv().await {
-                collected.entry(wire_id).or_default().push(share);
+        // t+1 shares per wire are enough, so up to n - (t+1) parties may never send theirs.
+        // the checked reconstructor waits for everyone, but settles for t+1 after a timeout
+        let wanted = match self.reconstructor {
+            Reconstructor::Interpolate => self.t + 1,
+            Reconstructor::Checked => self.n,
+        };
+        let recv_timeout = self.recv_timeout;
+        while collected.values().any(|v| v.len() < wanted) {
+            let received = self.recv_matching(|msg| match *msg {
+                Message::OutputShare(wire_id, share) if collected.contains_key(&wire_id) => Some((wire_id, share)),
+                _ => None,
+            });
+            let (wire_id, share) = match timeout(recv_timeout, received).await {
+                Ok(Ok(received)) => received,
+                Ok(Err(e)) => return Err(e),
+                Err(_) => {
+                    let short = collected.iter()
+                        .filter(|(_, v)| v.len() < self.t + 1)
+                        .min_by_key(|(&wire_id, _)| wire_id);
+                    match short {
+                        Some((&wire_id, shares)) => return Err(BgwError::InsufficientShares { wire_id, got: shares.len() }),
+                        None => break,
+                    }
+                }
+            };
+            let shares = collected.entry(wire_id).or_default();
+            if add_distinct(shares, share).is_err() {
+                return Err(self.abort(BgwError::ReconstructFailed { wire_id }.to_string()).await);
             }
         }
 
-        collected.into_iter()
-            .map(|(wire_id, shares)| (wire_id, shamir_reconstruct(&shares)))
-            .collect()
+        let outputs = if self.reconstructor == Reconstructor::Interpolate {
+            self.reconstruct_batch(collected)
+        } else {
+            let mut outputs = HashMap::new();
+            for (wire_id, shares) in collected {
+                match self.reconstruct_checked(&shares, self.t) {
+                    Ok(value) => outputs.insert(wire_id, value),
+                    Err(_) => return Err(self.abort(BgwError::ReconstructFailed { wire_id }.to_string()).await),
+                };
+            }
+            outputs
+        };
+        debug!(outputs = outputs.len(), "outputs reconstructed");
+        // the values themselves only at trace level, so they stay out of ordinary logs
+        trace!(?outputs, "output values");
+        Ok(outputs)
+    }
+
+    /// Interpolate f(0) for every wire in `collected`, looking up the Lagrange weights once per
+    /// distinct set of x-coordinates instead of once per wire: with the usual single set that is
+    /// O(n^2) for the weights plus O(n) per wire
+    fn reconstruct_batch(&mut self, collected: HashMap<usize, Vec<Share<F>>>) -> HashMap<usize, F> {
+        // x-set → the wires whose shares came from exactly those parties, with their y-values
+        let mut batches: HashMap<Vec<F>, Vec<(usize, Vec<F>)>> = HashMap::new();
+        for (wire_id, mut shares) in collected {
+            shares.sort_by_key(|s| s.x);
+            let xs = shares.iter().map(|s| s.x).collect();
+            let ys = shares.iter().map(|s| s.value).collect();
+            batches.entry(xs).or_default().push((wire_id, ys));
+        }
+
+        let mut outputs = HashMap::new();
+        for (xs, wires) in batches {
+            let weights = self.lagrange_cache.entry(xs).or_insert_with_key(|xs| LagrangeWeights::new(xs));
+            outputs.extend(wires.into_iter().map(|(wire_id, ys)| (wire_id, weights.reconstruct_with(&ys))));
+        }
+        outputs
+    }
+
+    /// Reveal `wire_id` to every party: broadcast our share and interpolate from the first t+1
+    /// that arrive (including our own)
+    pub async fn open(&mut self, wire_id: usize) -> Result<F, BgwError> {
+        let share = self.share(wire_id)?;
+        self.broadcast(Message::Open(wire_id, share)).await?;
+
+        let recv_timeout = self.recv_timeout;
+        let mut shares = vec![share];
+        while shares.len() < self.t + 1 {
+            let received = self.recv_matching(|msg| match *msg {
+                Message::Open(w, share) if w == wire_id => Some(share),
+                _ => None,
+            });
+            match timeout(recv_timeout, received).await {
+                Ok(Ok(share)) => {
+                    if add_distinct(&mut shares, share).is_err() {
+                        return Err(self.abort(BgwError::ReconstructFailed { wire_id }.to_string()).await);
+                    }
+                }
+                Ok(Err(e)) => return Err(e),
+                Err(_) => return Err(BgwError::Timeout { wire_id }),
+            }
+        }
+        Ok(self.reconstruct(&shares))
     }
 
-    fn eval_add(&mut self, out: usize, a: usize, b: usize) {
-        let s1 = self.shares[&a];
-        let s2 = self.shares[&b];
-        assert_eq!(s1.x, s2.x, "Mismatched x-values in addition");
+    /// Reveal `wire_id` to party `recipient` alone: everyone sends it their share, and it
+    /// interpolates from the first t+1 that arrive. the value at the recipient, `None` elsewhere
+    pub async fn open_to(&mut self, wire_id: usize, recipient: usize) -> Result<Option<F>, BgwError> {
+        let share = self.share(wire_id)?;
+        if recipient != self.id {
+            self.send(recipient, Message::OutputShare(wire_id, share)).await?;
+            return Ok(None);
+        }
+
+        let recv_timeout = self.recv_timeout;
+        let mut shares = vec![share];
+        while shares.len() < self.t + 1 {
+            let received = self.recv_matching(|msg| match *msg {
+                Message::OutputShare(w, share) if w == wire_id => Some(share),
+                _ => None,
+            });
+            match timeout(recv_timeout, received).await {
+                Ok(Ok(share)) => {
+                    if add_distinct(&mut shares, share).is_err() {
+                        return Err(self.abort(BgwError::ReconstructFailed { wire_id }.to_string()).await);
+                    }
+                }
+                Ok(Err(e)) => return Err(e),
+                Err(_) => return Err(BgwError::InsufficientShares { wire_id, got: shares.len() }),
+            }
+        }
+        Ok(Some(self.reconstruct(&shares)))
+    }
+
+    /// Like `shamir_reconstruct_checked` for a polynomial of the given degree, but using the
+    /// cached weights when there are no extra shares to check against
+    fn reconstruct_checked(&mut self, shares: &[Share<F>], degree: usize) -> Result<F, ReconstructError> {
+        if shares.len() == degree + 1 {
+            Ok(self.reconstruct(shares))
+        } else {
+            shamir_reconstruct_checked(shares, degree)
+        }
+    }
+
+    /// Interpolate f(0) from `shares`, reusing the Lagrange weights of any earlier
+    /// reconstruction over the same set of x-coordinates
+    fn reconstruct(&mut self, shares: &[Share<F>]) -> F {
+        let mut sorted = shares.to_vec();
+        sorted.sort_by_key(|s| s.x);
+        let xs: Vec<F> = sorted.iter().map(|s| s.x).collect();
+        let ys: Vec<F> = sorted.iter().map(|s| s.value).collect();
+
+        self.lagrange_cache.entry(xs)
+            .or_insert_with_key(|xs| LagrangeWeights::new(xs))
+            .reconstruct_with(&ys)
+    }
+
+    fn eval_add(&mut self, out: usize, a: usize, b: usize) -> Result<(), BgwError> {
+        let s1 = self.share(a)?;
+        let s2 = self.share(b)?;
+        if s1.x != s2.x {
+            return Err(BgwError::MismatchedShares { wire_id: out });
+        }
 
         self.shares.insert(out, Share {
             x: s1.x,
             value: s1.value + s2.value,
         });
+        Ok(())
     }
 
-    fn eval_const_mul(&mut self, out: usize, a: usize, c: Fr) {
-        let s = self.shares[&a];
+    /// Local affine combination `sum_i weight_i * share_i` with public weights (no communication)
+    pub fn weighted_sum(&self, terms: &[(usize, F)]) -> Result<Share<F>, BgwError> {
+        let x = self.x();
+        let mut shares = Vec::with_capacity(terms.len());
+        for &(wire_id, weight) in terms {
+            let s = self.share(wire_id)?;
+            if s.x != x {
+                return Err(BgwError::MismatchedShares { wire_id });
+            }
+            shares.push((s, weight));
+        }
+        Ok(self.affine(&shares, F::zero()))
+    }
+
+    fn eval_const_mul(&mut self, out: usize, a: usize, c: F) -> Result<(), BgwError> {
+        let s = self.share(a)?;
         self.shares.insert(out, Share {
             x: s.x,
             value: s.value * c,
         });
+        Ok(())
     }
 
-    pub async fn eval_mul(&mut self, out: usize, a: usize, b: usize) {
-        let s1 = self.shares[&a];
-        let s2 = self.shares[&b];
-        assert_eq!(s1.x, s2.x, "Mismatched x values for multiplication");
-    
-        // Step 1: Compute local product (degree 2t)
-        let local_product = Share {
-            x: s1.x,
-            value: s1.value * s2.value,
-        };
-    
-        // Step 2: Broadcast product shares to all other parties
-        for (&pid, tx) in &mut self.tx {
+    /// Every party adds `c` to its share: f(x) + c is a degree-t polynomial with value
+    /// secret + c at 0. (adding c at a single party is the rule for additive sharings; on a
+    /// Shamir sharing it would move only one point, and interpolation would not shift by c)
+    fn eval_add_const(&mut self, out: usize, a: usize, c: F) -> Result<(), BgwError> {
+        let s = self.share(a)?;
+        self.shares.insert(out, Share {
+            x: s.x,
+            value: s.value + c,
+        });
+        Ok(())
+    }
+
+    /// Shamir → additive: weight our point by its Lagrange coefficient over all n parties,
+    /// so the n resulting values sum to the secret. Linear gates keep working on the result,
+    /// but it must be converted back with `ToShamir` before a `Mul` or `Output`.
+    fn eval_to_additive(&mut self, out: usize, a: usize) -> Result<(), BgwError> {
+        let s = self.share(a)?;
+        if s.x != self.x() {
+            return Err(BgwError::MismatchedShares { wire_id: out });
+        }
+        let lambda = lagrange_coefficients(&self.x_coords)[self.id];
+        self.shares.insert(out, Share {
+            x: s.x,
+            value: s.value * lambda,
+        });
+        Ok(())
+    }
+
+    /// additive → Shamir: every party Shamir-shares its summand and everyone adds up the
+    /// n shares they receive, giving a degree-t sharing of the sum of the summands
+    async fn eval_to_shamir(&mut self, out: usize, a: usize) -> Result<(), BgwError> {
+        let summand = self.share(a)?.value;
+        let shares = shamir_share_at(summand, self.t, &self.x_coords);
+
+        for (pid, &share) in shares.iter().enumerate() {
             if pid != self.id {
-                tx.send(Message::MulShare(out, local_product))
-                    .await
-                    .expect("Failed to send multiplication share");
-            }
-        }
-    
-        // Step 3: Collect at least 2t + 1 distinct shares (including own)
-        let mut shares = vec![local_product];
-        while shares.len() < 2 * self.t + 1 {
-            if let Some(Message::MulShare(wire_id, share)) = self.rx.recv().await {
-                if wire_id == out && !shares.iter().any(|s| s.x == share.x) {
-                    shares.push(share);
-                }
-            }
-        }
-    
-        // Step 4: Reconstruct the product value
-        let product_value = shamir_reconstruct(&shares);
-        println!("Party {} reconstructed product value: {}", self.id, product_value);
-    
-        // Step 5: Reshare using Shamir (degree t)
-        let resharing_shares = shamir_share(product_value, self.t, self.n);
-    
-        // Step 6: Send each share to the corresponding party
-        for (&pid, tx) in &mut self.tx {
-            let share = resharing_shares[pid]; // intended for pid
+                self.send(pid, Message::ToShamirShare(out, share)).await?;
+            }
+        }
+
+        let recv_timeout = self.recv_timeout;
+        let mut value = shares[self.id].value;
+        for _ in 1..self.n {
+            let received = self.recv_matching(|msg| match *msg {
+                Message::ToShamirShare(wire_id, share) if wire_id == out => Some(share),
+                _ => None,
+            });
+            match timeout(recv_timeout, received).await {
+                Ok(Ok(share)) => value += share.value,
+                Ok(Err(e)) => return Err(e),
+                Err(_) => return Err(BgwError::Timeout { wire_id: out }),
+            }
+        }
+
+        self.shares.insert(out, Share { x: self.x(), value });
+        Ok(())
+    }
+
+    /// Coin tossing: every party shares a uniformly random value and everyone adds up the n
+    /// shares they receive, giving a degree-t sharing of a random value on `wire_id`. It stays
+    /// uniform and unknown to any coalition of up to t parties as long as one party is honest.
+    pub async fn shared_random(&mut self, wire_id: usize) -> Result<(), BgwError> {
+        let contribution = F::rand(&mut rand::thread_rng());
+        let shares = shamir_share_at(contribution, self.t, &self.x_coords);
+
+        for (pid, &share) in shares.iter().enumerate() {
             if pid != self.id {
-                tx.send(Message::Reshare(out, share))
-                    .await
-                    .expect("Failed to send resharing share");
-            }
-        }
-    
-        // Step 7: Receive resharing shares addressed to this party (same x each time)
-        let my_x = Fr::from((self.id + 1) as u64);
-        let mut final_shares = vec![resharing_shares[self.id]]; // include own
-        while final_shares.len() < self.n {
-            match timeout(Duration::from_secs(10), self.rx.recv()).await {
-                Ok(Some(Message::Reshare(wire_id, share)))
-                    if wire_id == out && share.x == my_x =>
-                {
-                    if !final_shares.iter().any(|s| s.x == share.x && s.value == share.value) {
-                        final_shares.push(share);
-                    }
+                self.send(pid, Message::RandShare(wire_id, share)).await?;
+            }
+        }
+
+        let recv_timeout = self.recv_timeout;
+        let mut value = shares[self.id].value;
+        for _ in 1..self.n {
+            let received = self.recv_matching(|msg| match *msg {
+                Message::RandShare(w, share) if w == wire_id => Some(share),
+                _ => None,
+            });
+            match timeout(recv_timeout, received).await {
+                Ok(Ok(share)) => value += share.value,
+                Ok(Err(e)) => return Err(e),
+                Err(_) => return Err(BgwError::Timeout { wire_id }),
+            }
+        }
+
+        self.shares.insert(wire_id, Share { x: self.x(), value });
+        Ok(())
+    }
+
+    /// Our share of a value privately dealt by party `dealer` on `wire_id`: the dealer passes
+    /// `Some(secret)` and shares it, everyone else passes `None` and waits for their share.
+    /// nothing is committed to, so a cheating dealer can hand out an inconsistent sharing
+    pub(crate) async fn deal(&mut self, dealer: usize, wire_id: usize, secret: Option<F>) -> Result<Share<F>, BgwError> {
+        if dealer == self.id {
+            let secret = secret.expect("the dealer must supply the secret");
+            let shares = shamir_share_at(secret, self.t, &self.x_coords);
+            for (pid, &share) in shares.iter().enumerate() {
+                if pid != self.id {
+                    self.send(pid, Message::RandShare(wire_id, share)).await?;
                 }
-                Ok(Some(_)) => {} // unrelated message, ignore
-                Ok(None) => {
-                    println!("Party {}: channel closed unexpectedly!", self.id);
-                    break;
+            }
+            return Ok(shares[self.id]);
+        }
+
+        let recv_timeout = self.recv_timeout;
+        let received = self.recv_matching(|msg| match *msg {
+            Message::RandShare(w, share) if w == wire_id => Some(share),
+            _ => None,
+        });
+        match timeout(recv_timeout, received).await {
+            Ok(result) => result,
+            Err(_) => Err(BgwError::Timeout { wire_id }),
+        }
+    }
+
+    /// Multiply using the next preprocessed triple (a, b, c): open d = x - a and e = y - b,
+    /// then x * y = c + d * b + e * a + d * e is computed locally. d and e are uniformly
+    /// masked, so nothing about x, y or their product is revealed, and it takes one round.
+    pub async fn eval_mul_beaver(&mut self, out: usize, x: usize, y: usize) -> Result<(), BgwError> {
+        let sx = self.share(x)?;
+        let sy = self.share(y)?;
+        let triple = self.triples.pop_front().ok_or(BgwError::NoTriplesLeft)?;
+        if sx.x != sy.x || sx.x != triple.a.x {
+            return Err(BgwError::MismatchedShares { wire_id: out });
+        }
+
+        self.metrics.mul_gates_evaluated += 1;
+        self.metrics.mul_rounds += 1;
+
+        let my_d = Share { x: sx.x, value: sx.value - triple.a.value };
+        let my_e = Share { x: sx.x, value: sy.value - triple.b.value };
+
+        // every party sends d before e, so the first opening we see from a sender is its d
+        self.broadcast(Message::Open(out, my_d)).await?;
+        self.broadcast(Message::Open(out, my_e)).await?;
+
+        let recv_timeout = self.recv_timeout;
+        let mut d_shares = vec![my_d];
+        let mut e_shares = vec![my_e];
+        while d_shares.len() < self.t + 1 || e_shares.len() < self.t + 1 {
+            let received = self.recv_matching(|msg| match *msg {
+                Message::Open(wire_id, share) if wire_id == out => Some(share),
+                _ => None,
+            });
+            let share = match timeout(recv_timeout, received).await {
+                Ok(Ok(share)) => share,
+                Ok(Err(e)) => return Err(e),
+                Err(_) => return Err(BgwError::Timeout { wire_id: out }),
+            };
+            if !d_shares.iter().any(|s| s.x == share.x) {
+                d_shares.push(share);
+            } else if add_distinct(&mut e_shares, share).is_err() {
+                return Err(self.abort(BgwError::ReconstructFailed { wire_id: out }.to_string()).await);
+            }
+        }
+
+        let d = self.reconstruct(&d_shares);
+        let e = self.reconstruct(&e_shares);
+
+        // d * e is public, so every party adds it to its share
+        self.shares.insert(out, Share {
+            x: sx.x,
+            value: triple.c.value + d * triple.b.value + e * triple.a.value + d * e,
+        });
+        Ok(())
+    }
+
+    pub async fn eval_mul(&mut self, out: usize, a: usize, b: usize) -> Result<(), BgwError> {
+        self.eval_mul_batch(&[(out, a, b)]).await
+    }
+
+    /// Evaluate independent multiplications `(out, a, b)` together: every step is done for all
+    /// of them before waiting on the next, so the whole batch costs the rounds of one `eval_mul`:
+    /// the resharing, plus the degree check when n > 2t + 1
+    ///
+    /// the local products lie on a degree-2t polynomial through (0, a·b). every party deals a
+    /// degree-t sharing of its own product, and everyone recombines the reshares of S, the 2t + 1
+    /// senders with the smallest x, with the Lagrange weights of S: a fresh degree-t sharing of
+    /// a·b that was never opened, not even to the resharers
+    ///
+    /// up to n - (2t + 1) parties may keep their reshares to themselves. a party waits for every
+    /// party not yet known to be absent, and returns as soon as they have all reshared; whoever
+    /// is still missing at `recv_timeout` is marked absent, so a silent party costs one timeout
+    /// per run rather than one per layer. a silent party is silent to everyone, so every party
+    /// marks the same ones absent and picks the same S; a party that reshares to some parties
+    /// and not others can split S, which this doesn't defend against
+    pub async fn eval_mul_batch(&mut self, gates: &[(usize, usize, usize)]) -> Result<(), BgwError> {
+        // the degree-2t product needs 2t + 1 points to be interpolated
+        if self.n < 2 * self.t + 1 {
+            return Err(BgwError::DegreeMismatch);
+        }
+        self.metrics.mul_gates_evaluated += gates.len();
+        self.metrics.mul_rounds += 1; // resharing
+
+        // Step 1: Compute local products (degree 2t)
+        let mut local_products = Vec::with_capacity(gates.len());
+        for &(out, a, b) in gates {
+            let s1 = self.share(a)?;
+            let s2 = self.share(b)?;
+            if s1.x != s2.x {
+                return Err(BgwError::MismatchedShares { wire_id: out });
+            }
+            local_products.push(s1.value * s2.value);
+        }
+
+        // Step 2: reshare every local product (degree t), sending each party its point
+        let my_x = self.x();
+        let mut received: Vec<HashMap<usize, F>> = vec![HashMap::new(); gates.len()]; // sender → share at our x
+        for (k, &(out, _, _)) in gates.iter().enumerate() {
+            let resharing_shares = shamir_share_at(local_products[k], self.t, &self.x_coords);
+            for (pid, &share) in resharing_shares.iter().enumerate() {
+                if pid != self.id {
+                    self.send(pid, Message::Reshare(out, self.id, share)).await?;
                 }
-                Err(_) => {
-                    println!("Party {}: Timeout waiting for resharing shares!", self.id);
-                    break;
-                }
-            }
-        }
-    
-        assert_eq!(
-            final_shares.len(),
-            self.n,
-            "Party {} did not receive all resharing shares for wire {}",
-            self.id,
-            out
-        );
-    
-        // ✅ Step 8: Sum values (since all have same x, different random masking)
-        let sum: Fr = final_shares.iter().map(|s| s.value).sum();
-        let inv_n = Fr::from(self.n as u64).inverse().unwrap(); // make sure n ≠ 0 mod p
-        let my_share_value = sum * inv_n;
-    
-        self.shares.insert(
-            out,
-            Share {
-                x: my_x,
-                value: my_share_value,
-            },
-        );
-    
-    }
-    
-    
+            }
+            received[k].insert(self.id, resharing_shares[self.id].value);
+        }
+
+        // Step 3: collect the reshares addressed to us, attributed by the sender id they carry,
+        // from every party not known to be absent or until the deadline
+        let slots: HashMap<usize, usize> = gates.iter().enumerate().map(|(k, &(out, _, _))| (out, k)).collect();
+        let expected: Vec<usize> = (0..self.n).filter(|pid| !self.absent.contains(pid)).collect();
+        let deadline = Instant::now() + self.recv_timeout;
+        while received.iter().any(|r| r.len() < expected.len()) {
+            let reshare = self.recv_matching(|msg| match *msg {
+                Message::Reshare(wire_id, from, share) if share.x == my_x && expected.contains(&from) => slots.get(&wire_id).map(|&k| (k, from, share)),
+                _ => None,
+            });
+            let (k, from, share) = match timeout_at(deadline, reshare).await {
+                Ok(Ok(reshare)) => reshare,
+                Ok(Err(e)) => return Err(e),
+                Err(_) => break,
+            };
+            match received[k].get(&from) {
+                Some(&value) if value != share.value => {
+                    return Err(self.abort(BgwError::ReconstructFailed { wire_id: gates[k].0 }.to_string()).await);
+                }
+                _ => {
+                    received[k].insert(from, share.value);
+                }
+            }
+        }
+
+        for pid in expected {
+            if received.iter().any(|r| !r.contains_key(&pid)) {
+                warn!(absent = pid, "no reshare by the deadline, not waiting for this party again");
+                self.absent.insert(pid);
+            }
+        }
+        for (k, &(out, _, _)) in gates.iter().enumerate() {
+            if received[k].len() < 2 * self.t + 1 {
+                return Err(BgwError::Timeout { wire_id: out });
+            }
+        }
+        self.check_product_degrees(gates, &received).await?;
+
+        for (k, &(out, _, _)) in gates.iter().enumerate() {
+            // Step 4: S is the 2t + 1 senders with the smallest x
+            let mut senders: Vec<usize> = received[k].keys().copied().collect();
+            senders.sort_by_key(|&pid| self.x_coords[pid]);
+            senders.truncate(2 * self.t + 1);
+            let xs: Vec<F> = senders.iter().map(|&pid| self.x_coords[pid]).collect();
+            let ys: Vec<F> = senders.iter().map(|pid| received[k][pid]).collect();
+
+            // Step 5: recombine with the Lagrange weights λ_i of S at 0. the local products r_i
+            // interpolate to a·b at 0, so \sum_{i in S} λ_i r_i = a·b, and the same combination
+            // of the degree-t reshares is a degree-t polynomial through (0, a·b)
+            // (S is sorted by x, so this is the same cache key a reconstruction over S would use)
+            let my_share_value = self.lagrange_cache.entry(xs)
+                .or_insert_with_key(|xs| LagrangeWeights::new(xs))
+                .reconstruct_with(&ys);
+
+            self.shares.insert(
+                out,
+                Share {
+                    x: my_x,
+                    value: my_share_value,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Check that the local products behind the reshares in `received` lie on a degree-2t
+    /// polynomial, aborting if any don't.
+    ///
+    /// with reshares from a set R of more than 2t + 1 parties there is room to check: for
+    /// distinct x_i, values r_i lie on a polynomial of degree 2t iff
+    /// \sum_{i in R} w_i x_i^m r_i = 0 for every m < |R| - (2t + 1), where
+    /// w_i = 1 / \prod_{j in R, j != i} (x_i - x_j). the same combination of the reshares at our x
+    /// is our share of a degree-t sharing of that sum, so each party broadcasts it, and the sum
+    /// is opened from the shares of every party not known to be absent (or those that arrive by
+    /// `recv_timeout`) and must be zero. honest sums are always zero, and the sharings are random
+    /// away from 0, so nothing else is revealed
+    async fn check_product_degrees(&mut self, gates: &[(usize, usize, usize)], received: &[HashMap<usize, F>]) -> Result<(), BgwError> {
+        let my_x = self.x();
+        let mut opened: HashMap<(usize, usize), Vec<Share<F>>> = HashMap::new(); // (wire, m) → shares of the sum
+        for (&(out, _, _), reshares) in gates.iter().zip(received) {
+            let redundancy = reshares.len().saturating_sub(2 * self.t + 1);
+            if redundancy == 0 {
+                continue;
+            }
+            let xs: Vec<F> = reshares.keys().map(|&pid| self.x_coords[pid]).collect();
+            let ys: Vec<F> = reshares.values().copied().collect();
+            // w_i x_i^m, starting at m = 0
+            let mut coefficients: Vec<F> = xs.iter().enumerate()
+                .map(|(i, &xi)| {
+                    let denominator: F = xs.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, &xj)| xi - xj).product();
+                    denominator.inverse().expect("x-coordinates are distinct")
+                })
+                .collect();
+            for m in 0..redundancy {
+                let value = coefficients.iter().zip(&ys).map(|(&c, &y)| c * y).sum();
+                opened.insert((out, m), vec![Share { x: my_x, value }]);
+                for (c, &x) in coefficients.iter_mut().zip(&xs) {
+                    *c *= x;
+                }
+            }
+        }
+        if opened.is_empty() {
+            return Ok(());
+        }
+        self.metrics.mul_rounds += 1;
+
+        let mut checks: Vec<(usize, usize, Share<F>)> = opened.iter().map(|(&(out, m), shares)| (out, m, shares[0])).collect();
+        checks.sort_by_key(|&(out, m, _)| (out, m));
+        for (out, m, share) in checks {
+            self.broadcast(Message::DegreeCheck(out, m, share)).await?;
+        }
+
+        let expected: Vec<usize> = (0..self.n).filter(|pid| !self.absent.contains(pid)).collect();
+        let expected_xs: Vec<F> = expected.iter().map(|&pid| self.x_coords[pid]).collect();
+        let deadline = Instant::now() + self.recv_timeout;
+        while opened.values().any(|shares| shares.len() < expected_xs.len()) {
+            let check = self.recv_matching(|msg| match *msg {
+                Message::DegreeCheck(out, m, share) if opened.contains_key(&(out, m)) && expected_xs.contains(&share.x) => Some((out, m, share)),
+                _ => None,
+            });
+            let (out, m, share) = match timeout_at(deadline, check).await {
+                Ok(Ok(check)) => check,
+                Ok(Err(e)) => return Err(e),
+                Err(_) => break,
+            };
+            let shares = opened.get_mut(&(out, m)).expect("only checks we started are accepted");
+            if add_distinct(shares, share).is_err() {
+                return Err(self.abort(BgwError::ReconstructFailed { wire_id: out }.to_string()).await);
+            }
+        }
+        for (pid, x) in expected.into_iter().zip(expected_xs) {
+            if opened.values().any(|shares| !shares.iter().any(|s| s.x == x)) {
+                warn!(absent = pid, "no degree check by the deadline, not waiting for this party again");
+                self.absent.insert(pid);
+            }
+        }
+
+        for ((out, _), shares) in opened {
+            match shamir_reconstruct_checked(&shares, self.t) {
+                Ok(sum) if sum.is_zero() => {}
+                Err(ReconstructError::TooFewShares { .. }) => return Err(BgwError::Timeout { wire_id: out }),
+                _ => return Err(self.abort(format!("reshares of wire {} are not of a degree-2t product", out)).await),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Add `share` to `shares` unless a share at the same x is already there.
+///
+/// A repeated point is harmless, but two different values at the same x mean the
+/// sender equivocated and nothing can be reconstructed from them.
+fn add_distinct<F: PartialEq + Copy>(shares: &mut Vec<Share<F>>, share: Share<F>) -> Result<(), Share<F>> {
+    match shares.iter().find(|s| s.x == share.x) {
+        None => {
+            shares.push(share);
+            Ok(())
+        }
+        Some(s) if s.value == share.value => Ok(()),
+        Some(&existing) => Err(existing),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use crate::sharing::{generate_beaver_triples, shamir_reconstruct, shamir_share, shamir_share_batch};
+    use ark_ff::{One, Zero};
+    use std::sync::{Arc, Mutex};
+
+    /// a party with no peers, for exercising the purely local operations
+    pub(crate) fn local_party(id: usize, n: usize, t: usize) -> Party<Fr> {
+        Party::with_config(id, PartyConfig::new(n, t), ChannelTransport::network(1).pop().unwrap())
+    }
+
+    /// n parties wired to each other's inboxes
+    pub(crate) fn network(n: usize, t: usize) -> Vec<Party<Fr>> {
+        ChannelTransport::network(n)
+            .into_iter()
+            .enumerate()
+            .map(|(id, transport)| Party::with_config(id, PartyConfig::new(n, t), transport))
+            .collect()
+    }
+
+    /// (a + b) * c with a, b, c owned by parties 0, 1, 2
+    fn sample_circuit() -> (Circuit<Fr>, usize) {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let b = circuit.add_gate(GateType::Input, None, None, Some(1));
+        let c = circuit.add_gate(GateType::Input, None, None, Some(2));
+        let sum = circuit.add_gate(GateType::Add, Some(a), Some(b), None);
+        let product = circuit.add_gate(GateType::Mul, Some(sum), Some(c), None);
+        let out = circuit.add_gate(GateType::Output, Some(product), None, None);
+        (circuit, out)
+    }
+
+    /// the sample circuit's inputs for a = 2, b = 3, c = 4, keyed by owning party
+    fn sample_inputs(pid: usize) -> HashMap<usize, Fr> {
+        match pid {
+            0..=2 => HashMap::from([(pid, Fr::from(pid as u64 + 2))]),
+            _ => HashMap::new(),
+        }
+    }
+
+    /// run every phase on each party concurrently, returning each party's reconstructed outputs
+    async fn run<T: Transport<Fr> + 'static>(parties: Vec<Party<Fr, T>>, circuit: &Circuit<Fr>, outputs: &[usize]) -> Vec<HashMap<usize, Fr>> {
+        let handles: Vec<_> = parties.into_iter().map(|mut party| {
+            let circuit = circuit.clone();
+            let outputs = outputs.to_vec();
+            tokio::spawn(async move {
+                party.input_phase(&circuit, &sample_inputs(party.id)).await.unwrap();
+                party.evaluate_circuit(&circuit).await.unwrap();
+                let output = party.output_phase(&circuit, &outputs).await.unwrap();
+                (party, output) // keep the inbox open until every party is done
+            })
+        }).collect();
+
+        let mut results = Vec::new();
+        for h in handles {
+            results.push(h.await.unwrap());
+        }
+        results.into_iter().map(|(_, output)| output).collect()
+    }
+
+    #[test]
+    fn test_weighted_sum_is_local() {
+        let (n, t) = (5, 2);
+        let a = shamir_share(Fr::from(4u64), t, n);
+        let b = shamir_share(Fr::from(10u64), t, n);
+        let c = shamir_share(Fr::from(7u64), t, n);
+
+        let weights = [Fr::from(2u64), Fr::from(3u64), Fr::from(5u64)];
+        let results: Vec<Share<Fr>> = (0..n)
+            .map(|pid| {
+                let mut party = local_party(pid, n, t);
+                party.shares.insert(0, a[pid]);
+                party.shares.insert(1, b[pid]);
+                party.shares.insert(2, c[pid]);
+                party.weighted_sum(&[(0, weights[0]), (1, weights[1]), (2, weights[2])]).unwrap()
+            })
+            .collect();
+
+        // 2*4 + 3*10 + 5*7 = 73
+        assert_eq!(shamir_reconstruct(&results[..t + 1]), Fr::from(73u64));
+    }
+
+    #[test]
+    fn test_weighted_sum_of_missing_wire_is_an_error() {
+        let party = local_party(0, 3, 1);
+        assert_eq!(party.weighted_sum(&[(9, Fr::from(1u64))]), Err(BgwError::MissingShare { wire_id: 9 }));
+    }
+
+    #[tokio::test]
+    async fn test_missing_input_value_is_an_error() {
+        let (circuit, _) = sample_circuit();
+        let mut party = network(5, 2).swap_remove(0);
+        let result = party.input_phase(&circuit, &HashMap::new()).await;
+        assert_eq!(result, Err(BgwError::MissingInput { wire_id: 0 }));
+    }
+
+    #[tokio::test]
+    async fn test_mul_without_enough_parties_is_an_error() {
+        let mut party = local_party(0, 3, 2);
+        let share = Share { x: party.x(), value: Fr::from(1u64) };
+        party.shares.insert(0, share);
+        party.shares.insert(1, share);
+        assert_eq!(party.eval_mul(2, 0, 1).await, Err(BgwError::DegreeMismatch));
+    }
+
+    #[tokio::test]
+    async fn test_progress_reaches_total() {
+        let (circuit, out) = sample_circuit();
+        // one mul layer: just the resharing when n = 2t + 1, and a degree check on top otherwise
+        for (n, t, rounds) in [(5, 2, 1), (4, 1, 2)] {
+            let mut parties = network(n, t);
+            let (progress_tx, mut progress_rx) = mpsc::channel(64);
+            parties[0].progress = Some(progress_tx);
+
+            for output in run(parties, &circuit, &[out]).await {
+                assert_eq!(output[&out], Fr::from(20u64));
+            }
+
+            let mut events = Vec::new();
+            while let Some(p) = progress_rx.recv().await {
+                events.push(p);
+            }
+            let last = events.last().unwrap();
+            assert_eq!(last.gates_done, last.gates_total);
+            assert_eq!(last.gates_total, circuit.gates.len());
+            assert_eq!(last.current_round, rounds);
+            assert!(events.windows(2).all(|w| w[0].gates_done < w[1].gates_done));
+        }
+    }
+
+    /// (recipient, message) for every message sent through a `Recording` transport
+    type SentLog = Arc<Mutex<Vec<(usize, Message<Fr>)>>>;
+
+    /// a channel transport that records every message it sends, with its recipient
+    struct Recording {
+        inner: ChannelTransport<Fr>,
+        sent: SentLog,
+    }
+
+    impl Transport<Fr> for Recording {
+        async fn send(&self, to: usize, msg: Envelope<Fr>) -> std::io::Result<()> {
+            self.sent.lock().unwrap().push((to, msg.msg.clone()));
+            self.inner.send(to, msg).await
+        }
+
+        async fn recv(&self) -> Option<Envelope<Fr>> {
+            self.inner.recv().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_beaver_multiplication_never_opens_products() {
+        // ((a * b) * c) * (a * c) with a, b, c = 2, 3, 4
+        let mut circuit = Circuit::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let b = circuit.add_gate(GateType::Input, None, None, Some(1));
+        let c = circuit.add_gate(GateType::Input, None, None, Some(2));
+        let ab = circuit.add_gate(GateType::Mul, Some(a), Some(b), None);
+        let abc = circuit.add_gate(GateType::Mul, Some(ab), Some(c), None);
+        let ac = circuit.add_gate(GateType::Mul, Some(a), Some(c), None);
+        let all = circuit.add_gate(GateType::Mul, Some(abc), Some(ac), None);
+        let out = circuit.add_gate(GateType::Output, Some(all), None, None);
+
+        let (n, t) = (5, 2);
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let triples = generate_beaver_triples(4, t, n);
+        let parties: Vec<_> = network(n, t).into_iter().zip(triples).map(|(party, triples)| Party {
+            triples: triples.into(),
+            ..Party::with_config(party.id, PartyConfig::new(n, t), Recording { inner: party.transport, sent: sent.clone() })
+        }).collect();
+
+        for output in run(parties, &circuit, &[out]).await {
+            assert_eq!(output[&out], Fr::from(192u64));
+        }
+
+        // only masked openings were exchanged during evaluation: no degree-2t product shares
+        // or reshares, and every opened value is unrelated to the intermediate products
+        let sent = sent.lock().unwrap();
+        let products = [6u64, 24, 8, 192].map(Fr::from);
+        assert!(!sent.iter().any(|(_, m)| matches!(m, Message::DegreeCheck(..) | Message::Reshare(..))));
+
+        // everything party 0 was sent is enough to reconstruct every opened value
+        let mut opened: HashMap<usize, Vec<Share<Fr>>> = HashMap::new();
+        for (to, msg) in sent.iter() {
+            if let (0, &Message::Open(wire_id, share)) = (to, msg) {
+                opened.entry(wire_id).or_default().push(share);
+            }
+        }
+        assert_eq!(opened.len(), 4);
+        for shares in opened.values() {
+            // each sender opened d then e; split them and reconstruct each
+            let (mut d, mut e) = (Vec::new(), Vec::new());
+            for &s in shares {
+                if d.iter().any(|x: &Share<Fr>| x.x == s.x) { e.push(s) } else { d.push(s) }
+            }
+            for value in [shamir_reconstruct(&d), shamir_reconstruct(&e)] {
+                assert!(!products.contains(&value));
+            }
+        }
+    }
+
+    #[test]
+    fn test_additive_shares_sum_to_secret() {
+        let (n, t) = (5, 2);
+        let secret = Fr::from(1234u64);
+        let shares = shamir_share(secret, t, n);
+
+        let sum: Fr = (0..n).map(|pid| {
+            let mut party = local_party(pid, n, t);
+            party.shares.insert(0, shares[pid]);
+            party.eval_to_additive(1, 0).unwrap();
+            party.shares[&1].value
+        }).sum();
+        assert_eq!(sum, secret);
+    }
+
+    #[tokio::test]
+    async fn test_shamir_to_additive_and_back() {
+        // 3 * (a + b) computed in the additive domain, then converted back
+        let mut circuit = Circuit::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let b = circuit.add_gate(GateType::Input, None, None, Some(1));
+        let a_add = circuit.add_gate(GateType::ToAdditive, Some(a), None, None);
+        let b_add = circuit.add_gate(GateType::ToAdditive, Some(b), None, None);
+        let sum = circuit.add_gate(GateType::Add, Some(a_add), Some(b_add), None);
+        let scaled = circuit.add_gate(GateType::ConstMul(Fr::from(3u64)), Some(sum), None, None);
+        let back = circuit.add_gate(GateType::ToShamir, Some(scaled), None, None);
+        let out = circuit.add_gate(GateType::Output, Some(back), None, None);
+        let a_back = circuit.add_gate(GateType::ToShamir, Some(a_add), None, None);
+        let a_out = circuit.add_gate(GateType::Output, Some(a_back), None, None);
+
+        for output in run(network(5, 2), &circuit, &[out, a_out]).await {
+            assert_eq!(output[&out], Fr::from(15u64));
+            assert_eq!(output[&a_out], Fr::from(2u64));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_absent_input_uses_default() {
+        // a + b + c where c's owner (party 3) never shows up
+        let mut circuit = Circuit::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let b = circuit.add_gate(GateType::Input, None, None, Some(1));
+        let c = circuit.add_input_with_default(3, Fr::from(5u64));
+        let ab = circuit.add_gate(GateType::Add, Some(a), Some(b), None);
+        let abc = circuit.add_gate(GateType::Add, Some(ab), Some(c), None);
+        let out = circuit.add_gate(GateType::Output, Some(abc), None, None);
+
+        let mut parties = network(4, 1);
+        let _absent = parties.pop().unwrap(); // keeps its inbox open but never runs
+
+        for party in &mut parties {
+            party.input_timeout = Duration::from_millis(100);
+        }
+
+        // 2 + 3 + default 5
+        for output in run(parties, &circuit, &[out]).await {
+            assert_eq!(output[&out], Fr::from(10u64));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_inconsistent_dealt_share_is_rejected() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+
+        let (t, n) = (1, 3);
+        let mut parties = network(n, t);
+        let mut dealer = parties.remove(0);
+
+        // party 0 deals honestly to party 2 but hands party 1 a point off the polynomial
+        let (mut shares, blinding, commitments) = shamir_share_with_commitments(Fr::from(7u64), t, &dealer.x_coords);
+        shares[1].value += Fr::one();
+        for pid in 1..n {
+            dealer.send(pid, Message::Commitments(a, commitments.clone())).await.unwrap();
+            dealer.send(pid, Message::InputShare(a, shares[pid], blinding[pid])).await.unwrap();
+        }
+
+        let mut honest = parties.pop().unwrap();
+        let mut cheated = parties.pop().unwrap();
+        assert_eq!(cheated.input_phase(&circuit, &HashMap::new()).await, Err(BgwError::InvalidShare { from: 0 }));
+        honest.input_phase(&circuit, &HashMap::new()).await.unwrap();
+        assert_eq!(honest.share(a), Ok(shares[2]));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_input_share_is_reported_before_evaluation() {
+        let (circuit, _) = sample_circuit();
+        let handles: Vec<_> = network(3, 1).into_iter().map(|mut party| {
+            let circuit = circuit.clone();
+            tokio::spawn(async move {
+                party.input_phase(&circuit, &sample_inputs(party.id)).await.unwrap();
+                party
+            })
+        }).collect();
+
+        let mut parties = Vec::new();
+        for h in handles {
+            parties.push(h.await.unwrap());
+        }
+        assert_eq!(parties[0].verify_all_inputs_present(&circuit), Ok(()));
+
+        // party 0 loses the share party 1 dealt it
+        parties[0].shares.remove(&1);
+        assert_eq!(parties[0].verify_all_inputs_present(&circuit), Err(BgwError::MissingShares { wire_ids: vec![1] }));
+        assert_eq!(parties[2].verify_all_inputs_present(&circuit), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_batched_output_matches_per_wire_reconstruction() {
+        let (n, t) = (5, 2);
+        let secrets: Vec<Fr> = (0..100u64).map(|v| Fr::from(v * v + 1)).collect();
+        let dealt = shamir_share_batch(&secrets, t, n);
+        let wires: Vec<usize> = (0..secrets.len()).collect();
+
+        // none of the wires are gates of this (empty) circuit, so each is opened to everyone
+        let handles: Vec<_> = network(n, t).into_iter().map(|mut party| {
+            for (wire_id, shares) in dealt.iter().enumerate() {
+                party.shares.insert(wire_id, shares[party.id]);
+            }
+            let wires = wires.clone();
+            tokio::spawn(async move {
+                let output = party.output_phase(&Circuit::new(), &wires).await.unwrap();
+                (party, output)
+            })
+        }).collect();
+
+        for h in handles {
+            let (party, output) = h.await.unwrap();
+            for (wire_id, shares) in dealt.iter().enumerate() {
+                assert_eq!(output[&wire_id], shamir_reconstruct(&shares[..t + 1]));
+                assert_eq!(output[&wire_id], secrets[wire_id]);
+            }
+            // the senders may differ from wire to wire, but never more than C(n, t+1) ways
+            assert!(party.lagrange_cache.len() <= 10);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rounds_larger_than_an_inbox_complete() {
+        // 600 products in one layer, each opened: every round sends each party 2 · 600 messages,
+        // more than the 1024 its inbox holds
+        let mut circuit = Circuit::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let b = circuit.add_gate(GateType::Input, None, None, Some(1));
+        let outs: Vec<usize> = (0..600u64).map(|i| {
+            let shifted = circuit.add_gate(GateType::AddConst(Fr::from(i)), Some(b), None, None);
+            let product = circuit.add_gate(GateType::Mul, Some(a), Some(shifted), None);
+            circuit.add_gate(GateType::Output, Some(product), None, None)
+        }).collect();
+
+        for output in run(network(3, 1), &circuit, &outs).await {
+            for (i, out) in outs.iter().enumerate() {
+                assert_eq!(output[out], Fr::from(2 * (3 + i as u64)));
+            }
+        }
+    }
+
+    /// forwards everything except the party's reshares of its local products
+    struct WithholdsReshares {
+        inner: ChannelTransport<Fr>,
+    }
+
+    impl Transport<Fr> for WithholdsReshares {
+        async fn send(&self, to: usize, msg: Envelope<Fr>) -> std::io::Result<()> {
+            match msg.msg {
+                Message::Reshare(..) => Ok(()),
+                _ => self.inner.send(to, msg).await,
+            }
+        }
+
+        async fn recv(&self) -> Option<Envelope<Fr>> {
+            self.inner.recv().await
+        }
+    }
+
+    /// input and evaluate on `party`, handing it back so its shares can be inspected and its
+    /// inbox outlives the run
+    async fn evaluate_one<T: Transport<Fr>>(mut party: Party<Fr, T>, circuit: Circuit<Fr>) -> Party<Fr, T> {
+        party.input_phase(&circuit, &sample_inputs(party.id)).await.unwrap();
+        party.evaluate_circuit(&circuit).await.unwrap();
+        party
+    }
+
+    /// run `circuit` with the parties in `silent` withholding their reshares, returning the shares
+    /// of `wire_id` held by everyone else
+    async fn shares_without_resharers(n: usize, t: usize, x_coords: &[Fr], silent: &[usize], circuit: &Circuit<Fr>, wire_id: usize) -> Vec<Share<Fr>> {
+        let config = PartyConfig { recv_timeout: Duration::from_millis(200), ..PartyConfig::new(n, t) };
+        let mut honest = Vec::new();
+        let mut withholding = Vec::new();
+        for (id, inner) in ChannelTransport::network(n).into_iter().enumerate() {
+            if silent.contains(&id) {
+                let party = Party::with_config(id, config.clone(), WithholdsReshares { inner }).with_x_coords(x_coords.to_vec()).unwrap();
+                withholding.push(tokio::spawn(evaluate_one(party, circuit.clone())));
+            } else {
+                let party = Party::with_config(id, config.clone(), inner).with_x_coords(x_coords.to_vec()).unwrap();
+                honest.push(tokio::spawn(evaluate_one(party, circuit.clone())));
+            }
+        }
+
+        let mut parties = Vec::new();
+        for h in honest {
+            parties.push(h.await.unwrap());
+        }
+        // the withholding parties recombine their own reshares too, so their S and their
+        // shares differ from everyone else's: they are only kept alive, never checked
+        let mut _withholding = Vec::new();
+        for h in withholding {
+            _withholding.push(h.await);
+        }
+        parties.iter().map(|party| party.share(wire_id).unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_mul_completes_without_a_silent_party() {
+        let (circuit, out) = sample_circuit();
+        let product = circuit.gates[out].left.unwrap();
+        let (n, t) = (4, 1);
+        let x_coords: Vec<Fr> = (1..=n as u64).map(Fr::from).collect();
+
+        // party 1 deals its input and evaluates, but never sends a reshare
+        let shares = shares_without_resharers(n, t, &x_coords, &[1], &circuit, product).await;
+        // the other three hold consistent degree-t shares of (2 + 3) * 4
+        assert_eq!(shamir_reconstruct_checked(&shares, t), Ok(Fr::from(20u64)));
+    }
+
+    /// everything a `tracing_subscriber::fmt` subscriber writes, for inspection
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_is_logged_without_the_secret() {
+        let logs = LogBuffer::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer({
+                let logs = logs.clone();
+                move || logs.clone()
+            })
+            .with_ansi(false)
+            .with_max_level(tracing::Level::INFO)
+            .finish();
+        // the test runtime is single-threaded, so every party's task logs here
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let mut circuit = Circuit::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let b = circuit.add_gate(GateType::Input, None, None, Some(1));
+        circuit.add_gate(GateType::Mul, Some(a), Some(b), None);
+        let values = [Fr::from(1234567u64), Fr::from(1000u64)];
+
+        // with n = 2t + 1, one party withholding its reshares leaves the others short
+        let (n, t) = (3, 1);
+        let config = PartyConfig { recv_timeout: Duration::from_millis(200), ..PartyConfig::new(n, t) };
+        let mut transports = ChannelTransport::network(n);
+        let mut silent = Party::with_config(2, config.clone(), WithholdsReshares { inner: transports.pop().unwrap() });
+        let withholding = tokio::spawn({
+            let circuit = circuit.clone();
+            async move {
+                silent.input_phase(&circuit, &HashMap::new()).await.unwrap();
+                let result = silent.evaluate_circuit(&circuit).await;
+                (silent, result) // kept alive until the others are done
+            }
+        });
+        let handles: Vec<_> = transports.into_iter().enumerate().map(|(id, transport)| {
+            let mut party = Party::with_config(id, config.clone(), transport);
+            let circuit = circuit.clone();
+            let inputs = HashMap::from([(id, values[id])]);
+            tokio::spawn(async move {
+                party.input_phase(&circuit, &inputs).await.unwrap();
+                let result = party.evaluate_circuit(&circuit).await;
+                (party, result)
+            })
+        }).collect();
+        let mut parties = Vec::new();
+        for h in handles {
+            let (party, result) = h.await.unwrap();
+            assert!(matches!(result, Err(BgwError::Timeout { .. })));
+            parties.push(party);
+        }
+        let _withholding = withholding.await;
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("WARN evaluate_circuit{party=0 run=0}"), "{}", logs);
+        assert!(logs.contains("absent=2"), "{}", logs);
+        assert!(logs.contains("timed out waiting for shares"), "{}", logs);
+        // neither the inputs nor their product 1234567000 show up at the default levels
+        assert!(!logs.contains("1234567"), "{}", logs);
+    }
+
+    #[tokio::test]
+    async fn test_mul_stops_waiting_for_an_absent_party() {
+        let (n, t) = (4, 1);
+        let recv_timeout = Duration::from_millis(500);
+        let config = PartyConfig { recv_timeout, ..PartyConfig::new(n, t) };
+        let mut parties: Vec<_> = ChannelTransport::network(n).into_iter().enumerate()
+            .map(|(id, transport)| Party::with_config(id, config.clone(), transport))
+            .collect();
+        // party 3 never sends anything, but keeps its inbox open
+        let _silent = parties.pop().unwrap();
+
+        let dealt = shamir_share_batch(&[Fr::from(3u64), Fr::from(4u64)], t, n);
+        let handles: Vec<_> = parties.into_iter().map(|mut party| {
+            party.set_share(0, dealt[0][party.id]);
+            party.set_share(1, dealt[1][party.id]);
+            tokio::spawn(async move {
+                // the first multiplication waits out the deadline, the second knows better
+                party.eval_mul(2, 0, 1).await.unwrap();
+                let start = Instant::now();
+                party.eval_mul(3, 2, 1).await.unwrap();
+                let elapsed = start.elapsed();
+                (party, elapsed)
+            })
+        }).collect();
+
+        let mut shares = Vec::new();
+        for h in handles {
+            let (party, elapsed) = h.await.unwrap();
+            assert_eq!(party.absent, HashSet::from([3]));
+            assert!(elapsed < recv_timeout / 5, "second multiplication took {:?}", elapsed);
+            shares.push(party.share(3).unwrap());
+        }
+        // 3 * 4 * 4
+        assert_eq!(shamir_reconstruct_checked(&shares, t), Ok(Fr::from(48u64)));
+    }
+
+    #[tokio::test]
+    async fn test_if_then_else_selects_a_branch() {
+        // cond ? (a + b) : (a * b), with cond, a, b owned by parties 0, 1, 2
+        let mut sum = Circuit::new();
+        let (x, y) = (sum.add_gate(GateType::Input, None, None, None), sum.add_gate(GateType::Input, None, None, None));
+        let x_plus_y = sum.add_gate(GateType::Add, Some(x), Some(y), None);
+        sum.add_gate(GateType::Output, Some(x_plus_y), None, None);
+        let mut product = Circuit::new();
+        let (x, y) = (product.add_gate(GateType::Input, None, None, None), product.add_gate(GateType::Input, None, None, None));
+        let x_times_y = product.add_gate(GateType::Mul, Some(x), Some(y), None);
+        product.add_gate(GateType::Output, Some(x_times_y), None, None);
+
+        let mut circuit = Circuit::new();
+        let cond = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let a = circuit.add_gate(GateType::Input, None, None, Some(1));
+        let b = circuit.add_gate(GateType::Input, None, None, Some(2));
+        let selected = circuit.if_then_else(cond, &sum, &product, &[a, b]);
+        let out = circuit.add_gate(GateType::Output, Some(selected[0]), None, None);
+        assert_eq!(circuit.validate(), Ok(()));
+
+        for (bit, expected) in [(1u64, 3 + 4), (0, 3 * 4)] {
+            let values = [Fr::from(bit), Fr::from(3u64), Fr::from(4u64)];
+            let handles: Vec<_> = network(3, 1).into_iter().map(|mut party| {
+                let circuit = circuit.clone();
+                let inputs = HashMap::from([(party.id, values[party.id])]);
+                tokio::spawn(async move {
+                    party.input_phase(&circuit, &inputs).await.unwrap();
+                    party.evaluate_circuit(&circuit).await.unwrap();
+                    let output = party.output_phase(&circuit, &[out]).await.unwrap();
+                    (party, output[&out])
+                })
+            }).collect();
+
+            let mut results = Vec::new();
+            for h in handles {
+                results.push(h.await.unwrap());
+            }
+            assert!(results.iter().all(|(_, output)| *output == Fr::from(expected)));
+        }
+    }
+
+    /// adds one to every reshare the party sends: a consistent degree-t sharing, but of its
+    /// local product plus one
+    struct ShiftsReshares {
+        inner: ChannelTransport<Fr>,
+    }
+
+    impl Transport<Fr> for ShiftsReshares {
+        async fn send(&self, to: usize, mut msg: Envelope<Fr>) -> std::io::Result<()> {
+            if let Message::Reshare(_, _, ref mut share) = msg.msg {
+                share.value += Fr::one();
+            }
+            self.inner.send(to, msg).await
+        }
+
+        async fn recv(&self) -> Option<Envelope<Fr>> {
+            self.inner.recv().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_degree_check_catches_a_bad_local_product() {
+        // n = 2t + 2: one reshare more than recombining needs, so the products can be checked
+        let (n, t) = (4, 1);
+        let a = shamir_share(Fr::from(3u64), t, n);
+        let b = shamir_share(Fr::from(4u64), t, n);
+        let mut transports = ChannelTransport::network(n);
+        let mut cheater = Party::with_config(0, PartyConfig::new(n, t), ShiftsReshares { inner: transports.remove(0) });
+        cheater.shares.insert(0, a[0]);
+        cheater.shares.insert(1, b[0]);
+        let cheater = tokio::spawn(async move {
+            let result = cheater.eval_mul(2, 0, 1).await;
+            (cheater, result)
+        });
+
+        let handles: Vec<_> = transports.into_iter().zip(1..).map(|(transport, id)| {
+            let mut party = Party::with_config(id, PartyConfig::new(n, t), transport);
+            party.shares.insert(0, a[id]);
+            party.shares.insert(1, b[id]);
+            tokio::spawn(async move {
+                let result = party.eval_mul(2, 0, 1).await;
+                (party, result)
+            })
+        }).collect();
+
+        let reason = "reshares of wire 2 are not of a degree-2t product".to_string();
+        let mut results = Vec::new();
+        for h in handles {
+            results.push(h.await.unwrap());
+        }
+        for (party, result) in &results {
+            assert_eq!(result, &Err(BgwError::ProtocolAborted(reason.clone())));
+            assert!(party.share(2).is_err());
+        }
+        let _cheater = cheater.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_degree_check_passes_honest_products() {
+        let (circuit, out) = sample_circuit();
+        let (n, t) = (7, 2);
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let parties: Vec<_> = network(n, t).into_iter().map(|party| {
+            Party::with_config(party.id, PartyConfig::new(n, t), Recording { inner: party.transport, sent: sent.clone() })
+        }).collect();
+
+        for output in run(parties, &circuit, &[out]).await {
+            assert_eq!(output[&out], Fr::from(20u64));
+        }
+        // 7 reshares where 5 are recombined: two sums checked, each broadcast by every party
+        let checks = sent.lock().unwrap().iter().filter(|(_, m)| matches!(m, Message::DegreeCheck(..))).count();
+        assert_eq!(checks, 2 * n * (n - 1));
+    }
+
+    #[tokio::test]
+    async fn test_mul_recombines_only_2t_plus_1_reshares() {
+        let (circuit, out) = sample_circuit();
+        let product = circuit.gates[out].left.unwrap();
+        let (n, t) = (5, 1);
+        // x-coordinates out of id order; parties 4 and 2 (the two smallest x) withhold their
+        // reshares, leaving exactly 2t + 1 from parties 0, 1 and 3
+        let x_coords: Vec<Fr> = [3u64, 7, 2, 9, 1].map(Fr::from).to_vec();
+
+        let shares = shares_without_resharers(n, t, &x_coords, &[2, 4], &circuit, product).await;
+        assert_eq!(shamir_reconstruct_checked(&shares, t), Ok(Fr::from(20u64)));
+    }
+
+    #[test]
+    fn test_x_coords_must_be_distinct_non_zero_and_one_per_party() {
+        let party = || local_party(0, 3, 1);
+        for bad in [vec![1u64, 2], vec![1, 2, 2], vec![0, 1, 2]] {
+            let result = party().with_x_coords(bad.into_iter().map(Fr::from).collect());
+            assert_eq!(result.err(), Some(BgwError::InvalidXCoords));
+        }
+        let party = party().with_x_coords([5u64, 3, 9].map(Fr::from).to_vec()).unwrap();
+        assert_eq!(party.x(), Fr::from(5u64));
+    }
+
+    #[tokio::test]
+    async fn test_mul_never_reveals_the_product_before_output() {
+        let (circuit, out) = sample_circuit();
+        let (n, t) = (5, 2);
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let parties: Vec<_> = network(n, t).into_iter().map(|party| {
+            Party::with_config(party.id, PartyConfig::new(n, t), Recording { inner: party.transport, sent: sent.clone() })
+        }).collect();
+        let x_coords = parties[0].x_coords.clone();
+
+        for output in run(parties, &circuit, &[out]).await {
+            assert_eq!(output[&out], Fr::from(20u64));
+        }
+
+        // until the output phase, every share a party is sent is a point at its own x: a single
+        // point of each degree-t polynomial, from which neither a·b nor anything about it follows
+        let sent = sent.lock().unwrap();
+        for (to, msg) in sent.iter() {
+            let share = match *msg {
+                Message::InputShare(_, share, _) | Message::Reshare(_, _, share) => share,
+                Message::OutputShare(..) | Message::Commitments(..) => continue,
+                ref other => panic!("unexpected message {:?}", other),
+            };
+            assert_eq!(share.x, x_coords[*to]);
+            assert_ne!(share.value, Fr::from(20u64));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_messages_from_another_run_are_ignored() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+
+        let (t, n) = (1, 3);
+        let mut parties = network(n, t);
+        for party in &mut parties {
+            party.run_id = 7;
+        }
+        let mut receiver = parties.pop().unwrap();
+        let mut dealer = parties.remove(0);
+
+        // a share and commitments replayed from run 6 arrive before the current ones
+        let (stale, stale_blinding, stale_commitments) = shamir_share_with_commitments(Fr::from(1u64), t, &dealer.x_coords);
+        for msg in [Message::Commitments(a, stale_commitments), Message::InputShare(a, stale[2], stale_blinding[2])] {
+            dealer.transport.send(2, Envelope { run_id: 6, msg }).await.unwrap();
+        }
+        let (shares, blinding, commitments) = shamir_share_with_commitments(Fr::from(2u64), t, &dealer.x_coords);
+        dealer.send(2, Message::Commitments(a, commitments)).await.unwrap();
+        dealer.send(2, Message::InputShare(a, shares[2], blinding[2])).await.unwrap();
+
+        receiver.input_phase(&circuit, &HashMap::new()).await.unwrap();
+        assert_eq!(receiver.share(a), Ok(shares[2]));
+        assert!(receiver.pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_owned_output_is_only_revealed_to_its_owner() {
+        let (mut circuit, out) = sample_circuit();
+        circuit.gates[out].owner = Some(0);
+        // a second, public output of the same value
+        let product = circuit.gates[out].left;
+        let public = circuit.add_gate(GateType::Output, product, None, None);
+
+        let outputs = run(network(5, 2), &circuit, &[out, public]).await;
+        assert_eq!(outputs[0][&out], Fr::from(20u64));
+        for (pid, output) in outputs.iter().enumerate() {
+            assert_eq!(output[&public], Fr::from(20u64));
+            if pid != 0 {
+                assert_eq!(output.get(&out), None);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_party_from_config_runs_sample_circuit() {
+        // an owned output under the public policy, reconstructed from every party's share
+        let (mut circuit, out) = sample_circuit();
+        circuit.gates[out].owner = Some(0);
+        let config = PartyConfig {
+            run_id: 7,
+            output_policy: OutputPolicy::Public,
+            reconstructor: Reconstructor::Checked,
+            ..PartyConfig::new(5, 2)
+        };
+        let parties: Vec<_> = ChannelTransport::network(5).into_iter().enumerate()
+            .map(|(id, transport)| Party::with_config(id, config.clone(), transport))
+            .collect();
+        assert_eq!(parties[3].x(), Fr::from(4u64));
+
+        for output in run(parties, &circuit, &[out]).await {
+            assert_eq!(output[&out], Fr::from(20u64));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_const() {
+        // a * 3 + 7 and a + 0 with a = 2
+        let mut circuit = Circuit::new();
+        let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+        let scaled = circuit.add_gate(GateType::ConstMul(Fr::from(3u64)), Some(a), None, None);
+        let shifted = circuit.add_gate(GateType::AddConst(Fr::from(7u64)), Some(scaled), None, None);
+        let out = circuit.add_gate(GateType::Output, Some(shifted), None, None);
+        let unchanged = circuit.add_gate(GateType::AddConst(Fr::zero()), Some(a), None, None);
+        let zero_out = circuit.add_gate(GateType::Output, Some(unchanged), None, None);
+
+        for output in run(network(5, 2), &circuit, &[out, zero_out]).await {
+            assert_eq!(output[&out], Fr::from(13u64));
+            assert_eq!(output[&zero_out], Fr::from(2u64));
+        }
+    }
+
+    /// a transport that delivers every message `latency` after it was sent
+    struct Lagged {
+        peers: Vec<mpsc::Sender<Envelope<Fr>>>,
+        inbox: tokio::sync::Mutex<mpsc::Receiver<Envelope<Fr>>>,
+        latency: Duration,
+    }
+
+    impl Transport<Fr> for Lagged {
+        async fn send(&self, to: usize, msg: Envelope<Fr>) -> std::io::Result<()> {
+            let tx = self.peers[to].clone();
+            let latency = self.latency;
+            tokio::spawn(async move {
+                tokio::time::sleep(latency).await;
+                let _ = tx.send(msg).await;
+            });
+            Ok(())
+        }
+
+        async fn recv(&self) -> Option<Envelope<Fr>> {
+            self.inbox.lock().await.recv().await
+        }
+    }
+
+    // the clock only moves while every party waits, so elapsed time counts latencies, not CPU time
+    #[tokio::test(start_paused = true)]
+    async fn test_input_phase_takes_one_round_for_all_owners() {
+        // every one of the n parties owns an input
+        let n = 5;
+        let mut circuit = Circuit::new();
+        for pid in 0..n {
+            circuit.add_gate(GateType::Input, None, None, Some(pid));
+        }
+
+        let latency = Duration::from_millis(50);
+        let (txs, rxs): (Vec<_>, Vec<_>) = (0..n).map(|_| mpsc::channel(1024)).unzip();
+        let handles: Vec<_> = rxs.into_iter().enumerate().map(|(pid, inbox)| {
+            let circuit = circuit.clone();
+            let transport = Lagged { peers: txs.clone(), inbox: inbox.into(), latency };
+            let mut party = Party::with_config(pid, PartyConfig::new(n, 2), transport);
+            tokio::spawn(async move {
+                let start = Instant::now();
+                party.input_phase(&circuit, &HashMap::from([(pid, Fr::from(pid as u64))])).await.unwrap();
+                let dealt = start.elapsed();
+                party.input_barrier().await.unwrap();
+                let synced = start.elapsed();
+                party.verify_all_inputs_present(&circuit).unwrap();
+                (party, dealt, synced)
+            })
+        }).collect();
+
+        for h in handles {
+            let (_, dealt, synced) = h.await.unwrap();
+            // one latency for the shares, one more for the barrier; dealing in turn would be n
+            assert!(dealt < 2 * latency, "input phase took {:?}", dealt);
+            assert!(synced < 3 * latency, "input phase and barrier took {:?}", synced);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_barrier_names_the_parties_that_never_arrive() {
+        let mut parties = network(3, 1);
+        let _absent = parties.pop().unwrap();
+        let handles: Vec<_> = parties.into_iter().map(|mut party| {
+            party.recv_timeout = Duration::from_millis(100);
+            tokio::spawn(async move {
+                let result = party.input_barrier().await;
+                (party, result)
+            })
+        }).collect();
+
+        for h in handles {
+            let (_, result) = h.await.unwrap();
+            assert_eq!(result, Err(BgwError::BarrierTimeout { missing: vec![2] }));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_independent_muls_in_a_layer_share_their_rounds() {
+        // a*b, c*d, a*c and b*d all sit on the same layer
+        let mut circuit = Circuit::new();
+        let inputs: Vec<usize> = (0..4).map(|pid| circuit.add_gate(GateType::Input, None, None, Some(pid))).collect();
+        let outs: Vec<usize> = [(0, 1), (2, 3), (0, 2), (1, 3)].iter().map(|&(l, r)| {
+            let product = circuit.add_gate(GateType::Mul, Some(inputs[l]), Some(inputs[r]), None);
+            circuit.add_gate(GateType::Output, Some(product), None, None)
+        }).collect();
+
+        let (n, t) = (4, 1);
+        let latency = Duration::from_millis(50);
+        let (txs, rxs): (Vec<_>, Vec<_>) = (0..n).map(|_| mpsc::channel(1024)).unzip();
+        let handles: Vec<_> = network(n, t).into_iter().zip(rxs).map(|(party, inbox)| {
+            let circuit = circuit.clone();
+            let outs = outs.clone();
+            let transport = Lagged { peers: txs.clone(), inbox: inbox.into(), latency };
+            let mut party = Party::with_config(party.id, PartyConfig::new(n, t), transport);
+            tokio::spawn(async move {
+                let inputs = HashMap::from([(party.id, Fr::from(party.id as u64 + 2))]);
+                party.input_phase(&circuit, &inputs).await.unwrap();
+                let start = Instant::now();
+                party.evaluate_circuit(&circuit).await.unwrap();
+                let elapsed = start.elapsed();
+                let output = party.output_phase(&circuit, &outs).await.unwrap();
+                (party, output, elapsed)
+            })
+        }).collect();
+
+        for h in handles {
+            let (_, output, elapsed) = h.await.unwrap();
+            // 2*3, 4*5, 2*4, 3*5
+            for (&out, expected) in outs.iter().zip([6u64, 20, 8, 15]) {
+                assert_eq!(output[&out], Fr::from(expected));
+            }
+            // one resharing and one degree check in total; one after another would be 8 latencies
+            assert!(elapsed < 4 * latency, "evaluation took {:?}", elapsed);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics_count_messages_per_mul() {
+        let (circuit, out) = sample_circuit();
+        let (n, t) = (5, 2);
+        let handles: Vec<_> = network(n, t).into_iter().map(|mut party| {
+            let circuit = circuit.clone();
+            tokio::spawn(async move {
+                party.input_phase(&circuit, &sample_inputs(party.id)).await.unwrap();
+                let before = *party.metrics();
+                party.evaluate_circuit(&circuit).await.unwrap();
+                let during = *party.metrics();
+                party.output_phase(&circuit, &[out]).await.unwrap();
+                (party, before, during)
+            })
+        }).collect();
+
+        for h in handles {
+            let (party, before, during) = h.await.unwrap();
+            assert_eq!(during.mul_gates_evaluated, 1);
+            assert_eq!(during.mul_rounds, 1);
+            // one reshare to every other party (with n = 2t + 1 every party is a resharer)
+            assert_eq!(during.messages_sent - before.messages_sent, n - 1);
+            assert!(during.bytes_sent > before.bytes_sent);
+            // and one output share to everyone else at the end
+            assert_eq!(party.metrics().messages_sent, during.messages_sent + n - 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_output_survives_a_dropped_party() {
+        let (circuit, out) = sample_circuit();
+        let (n, t) = (4, 1);
+        let mut parties = network(n, t);
+        let _dropped = parties.pop().unwrap(); // keeps its inbox open but never runs
+        for party in &mut parties {
+            party.recv_timeout = Duration::from_millis(200); // how long each mul waits for its reshares
+        }
+
+        for output in run(parties, &circuit, &[out]).await {
+            assert_eq!(output[&out], Fr::from(20u64));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shared_random_is_agreed_on_and_fresh() {
+        let circuit = Circuit::new();
+        let mut values = Vec::new();
+        for _ in 0..2 {
+            let handles: Vec<_> = network(5, 2).into_iter().map(|mut party| {
+                let circuit = circuit.clone();
+                tokio::spawn(async move {
+                    party.shared_random(0).await.unwrap();
+                    let output = party.output_phase(&circuit, &[0]).await.unwrap();
+                    (party, output[&0])
+                })
+            }).collect();
+
+            let mut results = Vec::new();
+            for h in handles {
+                results.push(h.await.unwrap()); // keep every party alive until all are done
+            }
+            assert!(results.iter().all(|(_, v)| *v == results[0].1));
+            values.push(results[0].1);
+        }
+        // two runs give different values (equal with probability 1/p)
+        assert_ne!(values[0], values[1]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_degree_check_aborts_every_party() {
+        let (circuit, out) = sample_circuit();
+        let product = circuit.gates[out].left.unwrap();
+        // n >= 2t + 2, so every multiplication is degree-checked
+        let (n, t) = (5, 1);
+        let mut transports = ChannelTransport::network(n);
+        let cheater = Party::with_config(0, PartyConfig::new(n, t), ShiftsReshares { inner: transports.remove(0) });
+
+        async fn evaluate<T: Transport<Fr>>(mut party: Party<Fr, T>, circuit: Circuit<Fr>) -> (Party<Fr, T>, Result<(), BgwError>) {
+            party.input_phase(&circuit, &sample_inputs(party.id)).await.unwrap();
+            let result = party.evaluate_circuit(&circuit).await;
+            (party, result)
+        }
+        let cheater = tokio::spawn(evaluate(cheater, circuit.clone()));
+        let honest: Vec<_> = transports.into_iter().zip(1..).map(|(transport, id)| {
+            tokio::spawn(evaluate(Party::with_config(id, PartyConfig::new(n, t), transport), circuit.clone()))
+        }).collect();
+
+        // whoever notices first aborts, and the rest stop with its reason, the cheater included
+        let reason = format!("reshares of wire {} are not of a degree-2t product", product);
+        let mut results = Vec::new();
+        for h in honest {
+            results.push(h.await.unwrap());
+        }
+        for (_, result) in &results {
+            assert_eq!(result, &Err(BgwError::ProtocolAborted(reason.clone())));
+        }
+        let (_cheater, result) = cheater.await.unwrap();
+        assert_eq!(result, Err(BgwError::ProtocolAborted(reason)));
+    }
+
+    #[tokio::test]
+    async fn test_inconsistency_aborts_every_party() {
+        let (n, t) = (3, 1);
+        let a = shamir_share(Fr::from(3u64), t, n);
+        let b = shamir_share(Fr::from(4u64), t, n);
+        let mut parties = network(n, t);
+        for party in &mut parties {
+            party.shares.insert(0, a[party.id]);
+            party.shares.insert(1, b[party.id]);
+        }
+
+        // party 0 equivocates: two different reshares to party 1, and none to party 2
+        let mut cheater = parties.remove(0);
+        let reshares = shamir_share_at(a[0].value * b[0].value, t, &cheater.x_coords);
+        let forged = Share { value: reshares[1].value + Fr::one(), ..reshares[1] };
+        cheater.send(1, Message::Reshare(2, 0, reshares[1])).await.unwrap();
+        cheater.send(1, Message::Reshare(2, 0, forged)).await.unwrap();
+
+        let handles: Vec<_> = parties.into_iter().map(|mut party| tokio::spawn(async move {
+            let result = party.eval_mul(2, 0, 1).await;
+            (party, result)
+        })).collect();
+
+        // party 1 detects the conflict; everyone, including the cheater, ends up aborted
+        let reason = BgwError::ReconstructFailed { wire_id: 2 }.to_string();
+        let mut results = Vec::new();
+        for h in handles {
+            results.push(h.await.unwrap());
+        }
+        for (_, result) in &results {
+            assert_eq!(result, &Err(BgwError::ProtocolAborted(reason.clone())));
+        }
+        assert_eq!(cheater.recv_matching(|_| None::<()>).await, Err(BgwError::ProtocolAborted(reason)));
+    }
 }
@@ -1,6 +1,8 @@
-use ark_bn254::Fr;
-use ark_ff::{Field, UniformRand, One, Zero};
+use ark_ec::Group;
+use ark_ff::{batch_inversion, One, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use rand::{thread_rng};
+use std::fmt::Debug;
 
 // shamir secret sharing reference: https://evervault.com/blog/shamir-secret-sharing
 // polynomial interpolation reference: https://vitalik.eth.limo/general/2016/12/10/qap.html
@@ -11,16 +13,154 @@ use rand::{thread_rng};
 /// Each share is a point (x, f(x)) on a t-degree polynomial f(x)
 /// where f(0) is the secret being shared.
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Share {
-    pub x: Fr,
-    pub value: Fr,
+pub struct Share<F> {
+    pub x: F,
+    pub value: F,
 }
 
-/// generate t-degree polynomial f(x) with f(0) = secret
-pub fn shamir_share(secret: Fr, t: usize, n: usize) -> Vec<Share> {
+/// A prime field the protocol can run over, together with the commitments dealers publish
+/// so that receivers can check their input shares (see `shamir_share_with_commitments`).
+/// The implementations live in `fields`.
+pub trait ProtocolField: PrimeField {
+    type Commitment: Clone + Debug + PartialEq + Send + Sync + CanonicalSerialize + CanonicalDeserialize + 'static;
+
+    /// commitments to the coefficients a_0, ..., a_t of a sharing polynomial, each hidden by
+    /// the matching coefficient b_j of a random blinding polynomial
+    fn commit(coefficients: &[Self], blinding: &[Self]) -> Vec<Self::Commitment>;
+
+    /// whether `share` lies on the polynomial committed to, given `blinding`, the blinding
+    /// polynomial at the same x
+    fn verify(share: &Share<Self>, blinding: Self, commitments: &[Self::Commitment]) -> bool;
+}
+
+/// Pedersen commitments g^{a_j} h^{b_j} in a group of the field's order, where nobody may
+/// know log_g h
+pub fn pedersen_commit<G: Group>(h: G, coefficients: &[G::ScalarField], blinding: &[G::ScalarField]) -> Vec<G> {
+    let g = G::generator();
+    coefficients.iter().zip(blinding).map(|(&a, &b)| g * a + h * b).collect()
+}
+
+/// check g^{f(x)} h^{r(x)} = \prod_j C_j^{x^j} for Pedersen commitments C_j, with r the
+/// blinding polynomial
+pub fn pedersen_verify<G: Group>(h: G, share: &Share<G::ScalarField>, blinding: G::ScalarField, commitments: &[G]) -> bool {
+    let mut expected = G::zero();
+    let mut power = G::ScalarField::one();
+    for &c in commitments {
+        expected += c * power;
+        power *= share.x;
+    }
+    G::generator() * share.value + h * blinding == expected
+}
+
+/// size of an encoded field element (canonical little-endian integer)
+pub fn field_bytes<F: PrimeField>() -> usize {
+    F::zero().compressed_size()
+}
+
+/// canonical little-endian encoding of a field element
+pub fn field_to_bytes<F: PrimeField>(value: &F) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(field_bytes::<F>());
+    value.serialize_compressed(&mut bytes).expect("writing to a Vec can't fail");
+    bytes
+}
+
+/// decode a field element, rejecting encodings that are not reduced modulo p
+pub fn field_from_bytes<F: PrimeField>(bytes: &[u8]) -> Option<F> {
+    if bytes.len() != field_bytes::<F>() {
+        return None;
+    }
+    F::deserialize_compressed(bytes).ok()
+}
+
+impl<F: PrimeField> Share<F> {
+    /// size of an encoded share: x followed by f(x)
+    pub fn encoded_len() -> usize {
+        2 * field_bytes::<F>()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = field_to_bytes(&self.x);
+        bytes.extend_from_slice(&field_to_bytes(&self.value));
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Share<F>> {
+        if bytes.len() != Self::encoded_len() {
+            return None;
+        }
+        let (x, value) = bytes.split_at(field_bytes::<F>());
+        Some(Share {
+            x: field_from_bytes(x)?,
+            value: field_from_bytes(value)?,
+        })
+    }
+}
+
+/// One party's shares of a multiplication triple (a, b, c) with c = a * b
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BeaverTriple<F> {
+    pub a: Share<F>,
+    pub b: Share<F>,
+    pub c: Share<F>,
+}
+
+/// trusted-dealer preprocessing: `count` random triples shared among n parties at x = 1, ..., n
+///
+/// returns triples[party][k], the party's share of the k-th triple
+pub fn generate_beaver_triples<F: PrimeField>(count: usize, t: usize, n: usize) -> Vec<Vec<BeaverTriple<F>>> {
     let mut rng = thread_rng();
+    let mut triples = vec![Vec::with_capacity(count); n];
+
+    for _ in 0..count {
+        let a = F::rand(&mut rng);
+        let b = F::rand(&mut rng);
+        let a_shares = shamir_share(a, t, n);
+        let b_shares = shamir_share(b, t, n);
+        let c_shares = shamir_share(a * b, t, n);
+
+        for (pid, party_triples) in triples.iter_mut().enumerate() {
+            party_triples.push(BeaverTriple { a: a_shares[pid], b: b_shares[pid], c: c_shares[pid] });
+        }
+    }
+    triples
+}
+
+/// generate t-degree polynomial f(x) with f(0) = secret, evaluated at x = 1, ..., n
+pub fn shamir_share<F: PrimeField>(secret: F, t: usize, n: usize) -> Vec<Share<F>> {
+    let xs: Vec<F> = (1..=n).map(|i| F::from(i as u64)).collect();
+    shamir_share_at(secret, t, &xs)
+}
+
+/// generate t-degree polynomial f(x) with f(0) = secret, evaluated at the given x-coordinates
+///
+/// the x-coordinates must be distinct and non-zero (f(0) is the secret itself)
+pub fn shamir_share_at<F: PrimeField>(secret: F, t: usize, xs: &[F]) -> Vec<Share<F>> {
+    evaluate_shares(&random_polynomial(secret, t), xs)
+}
+
+/// like `shamir_share_at`, but also returns every party's point on a random blinding polynomial
+/// and the field's commitments to both polynomials' coefficients (Pedersen commitments
+/// g^{a_j} h^{b_j} for BN254), so each receiver can check its share with `verify_share`
+///
+/// the blinding makes the commitments independent of the secret: even a secret with little
+/// entropy can't be found by trying candidates against them
+pub fn shamir_share_with_commitments<F: ProtocolField>(secret: F, t: usize, xs: &[F]) -> (Vec<Share<F>>, Vec<F>, Vec<F::Commitment>) {
+    let coefficients = random_polynomial(secret, t);
+    let blinding = random_polynomial(F::rand(&mut thread_rng()), t);
+    let commitments = F::commit(&coefficients, &blinding);
+    let blinding_values = evaluate_shares(&blinding, xs).into_iter().map(|s| s.value).collect();
+    (evaluate_shares(&coefficients, xs), blinding_values, commitments)
+}
 
-    // 1. builds a random polynomial: f(x) = a_0 + a_1x + ... + a_tx^t
+/// check that `share`, with its point `blinding` on the blinding polynomial, lies on the
+/// polynomial committed to
+pub fn verify_share<F: ProtocolField>(share: &Share<F>, blinding: F, commitments: &[F::Commitment]) -> bool {
+    F::verify(share, blinding, commitments)
+}
+
+/// f(x) = a_0 + a_1x + ... + a_tx^t with a_0 = secret and a_1, ..., a_t random
+fn random_polynomial<F: PrimeField>(secret: F, t: usize) -> Vec<F> {
+    let mut rng = thread_rng();
 
     // a_0 = secret
     let mut coefficients = vec![secret];
@@ -28,17 +168,20 @@ pub fn shamir_share(secret: Fr, t: usize, n: usize) -> Vec<Share> {
     // a_1, ..., a_t are random coefficients
     // note: t+1 coefficients needed for a t-degree polynomial
     for _ in 0..t {
-        coefficients.push(Fr::rand(&mut rng))
+        coefficients.push(F::rand(&mut rng))
     }
+    coefficients
+}
 
-    // 2. evaluates the polynomial f(x) at x = 1, ..., n to generate n shares
+/// evaluate the polynomial f(x) at each x_i to generate one share per point
+fn evaluate_shares<F: PrimeField>(coefficients: &[F], xs: &[F]) -> Vec<Share<F>> {
     let mut shares = Vec::new();
-    for i in 1..=n {
-        // x_i
-        let x = Fr::from(i as u64);
+    for (i, &x) in xs.iter().enumerate() {
+        assert!(!x.is_zero(), "x-coordinate 0 would reveal the secret!");
+        assert!(!xs[..i].contains(&x), "Duplicate x-coordinate {}", x);
 
         // f(x_i)
-        let mut fx = Fr::zero();
+        let mut fx = F::zero();
         for (j, coef) in coefficients.iter().enumerate() {
             fx += *coef * x.pow([j as u64]);
         }
@@ -48,16 +191,104 @@ pub fn shamir_share(secret: Fr, t: usize, n: usize) -> Vec<Share> {
     shares
 }
 
+/// share every secret in `secrets` at x = 1, ..., n; returns shares[secret][party]
+///
+/// the powers x_i^j are computed once for all secrets and one RNG is reused throughout
+pub fn shamir_share_batch<F: PrimeField>(secrets: &[F], t: usize, n: usize) -> Vec<Vec<Share<F>>> {
+    let mut rng = thread_rng();
+
+    // powers[i][j] = x_i^j for j = 0, ..., t
+    let xs: Vec<F> = (1..=n).map(|i| F::from(i as u64)).collect();
+    let powers: Vec<Vec<F>> = xs.iter().map(|&x| {
+        std::iter::successors(Some(F::one()), |&p| Some(p * x)).take(t + 1).collect()
+    }).collect();
+
+    let mut coefficients = vec![F::zero(); t + 1];
+    secrets.iter().map(|&secret| {
+        coefficients[0] = secret;
+        for coef in &mut coefficients[1..] {
+            *coef = F::rand(&mut rng);
+        }
+        xs.iter().zip(&powers).map(|(&x, row)| Share {
+            x,
+            value: coefficients.iter().zip(row).map(|(&a, &p)| a * p).sum(),
+        }).collect()
+    }).collect()
+}
+
+/// reconstruct every secret from its shares; shares[k] holds the k-th secret's shares
+///
+/// all secrets must be shared at the same x-coordinates (in the same order), so the
+/// Lagrange coefficients are computed once and reused for each secret
+pub fn shamir_reconstruct_batch<F: PrimeField>(shares: &[Vec<Share<F>>]) -> Vec<F> {
+    let Some(first) = shares.first() else { return Vec::new() };
+    let xs: Vec<F> = first.iter().map(|s| s.x).collect();
+    let lambdas = lagrange_coefficients(&xs);
+
+    shares.iter().map(|secret_shares| {
+        assert!(secret_shares.iter().map(|s| s.x).eq(xs.iter().copied()), "batch shares use different x-coordinates");
+        secret_shares.iter().zip(&lambdas).map(|(s, &lambda)| s.value * lambda).sum()
+    }).collect()
+}
+
+/// lagrange basis polynomials evaluated at x=0: ℓ_i(0) = \prod_{j != i} x_j / (x_j - x_i)
+///
+/// f(0) = \sum f(x_i) * ℓ_i(0) for any polynomial of degree < xs.len()
+pub fn lagrange_coefficients<F: PrimeField>(xs: &[F]) -> Vec<F> {
+    LagrangeWeights::new(xs).weights
+}
+
+/// The weights ℓ_i(0) for a fixed set of x-coordinates, computed once so that every
+/// later reconstruction over the same points is a single dot product
+#[derive(Debug, Clone, PartialEq)]
+pub struct LagrangeWeights<F> {
+    xs: Vec<F>,
+    weights: Vec<F>, // weights[i] = ℓ_i(0)
+}
+
+impl<F: PrimeField> LagrangeWeights<F> {
+    /// panics on duplicate x-coordinates, which no polynomial can be interpolated through
+    pub fn new(xs: &[F]) -> LagrangeWeights<F> {
+        let mut numerators = vec![F::one(); xs.len()];
+        let mut denominators = vec![F::one(); xs.len()];
+        for (i, &xi) in xs.iter().enumerate() {
+            for (j, &xj) in xs.iter().enumerate() {
+                if i != j {
+                    numerators[i] *= xj;
+                    denominators[i] *= xj - xi;
+                }
+            }
+        }
+        assert!(denominators.iter().all(|d| !d.is_zero()), "Division by zero: Duplicate x values!");
+
+        // one inversion for all the denominators
+        batch_inversion(&mut denominators);
+        let weights = numerators.iter().zip(&denominators).map(|(&num, &inv)| num * inv).collect();
+        LagrangeWeights { xs: xs.to_vec(), weights }
+    }
+
+    /// the x-coordinates these weights interpolate over
+    pub fn xs(&self) -> &[F] {
+        &self.xs
+    }
+
+    /// f(0) given ys[i] = f(xs[i]), for any f of degree < xs.len()
+    pub fn reconstruct_with(&self, ys: &[F]) -> F {
+        assert_eq!(ys.len(), self.weights.len(), "need one value per x-coordinate");
+        ys.iter().zip(&self.weights).map(|(&y, &w)| y * w).sum()
+    }
+}
+
 /// lagrange interpolation at x=0
-pub fn shamir_reconstruct(shares: &[Share]) -> Fr {
-    let mut secret = Fr::zero();
+pub fn shamir_reconstruct<F: PrimeField>(shares: &[Share<F>]) -> F {
+    let mut secret = F::zero();
 
     for (i, si) in shares.iter().enumerate() {
         let xi = si.x;
         let yi = si.value;
 
-        let mut num = Fr::one();
-        let mut den = Fr::one();
+        let mut num = F::one();
+        let mut den = F::one();
 
         // lagrange basis polynomial evaluated at 0: ℓ_i(0) = \prod_{j=1, j != i}^k x_j / (x_j - x_i)
         for (j, sj) in shares.iter().enumerate() {
@@ -78,10 +309,116 @@ pub fn shamir_reconstruct(shares: &[Share]) -> Fr {
     secret
 }
 
+/// why a checked reconstruction was refused
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReconstructError {
+    TooFewShares { have: usize, need: usize },
+    DuplicateX,      // two shares at the same x-coordinate
+    DegreeTooHigh,   // the points don't all lie on one polynomial of the expected degree
+}
+
+impl std::fmt::Display for ReconstructError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReconstructError::TooFewShares { have, need } => write!(f, "need {} shares, have {}", need, have),
+            ReconstructError::DuplicateX => write!(f, "duplicate x-coordinate in shares"),
+            ReconstructError::DegreeTooHigh => write!(f, "shares do not lie on a polynomial of the expected degree"),
+        }
+    }
+}
+
+impl std::error::Error for ReconstructError {}
+
+/// interpolate f(0) through the first t+1 shares, then check every remaining share lies on
+/// the same degree-t polynomial
+///
+/// with exactly t+1 shares there is nothing to cross-check, so a bad share can only be caught
+/// when extra shares are supplied
+pub fn shamir_reconstruct_checked<F: PrimeField>(shares: &[Share<F>], t: usize) -> Result<F, ReconstructError> {
+    if shares.len() < t + 1 {
+        return Err(ReconstructError::TooFewShares { have: shares.len(), need: t + 1 });
+    }
+    for (i, s) in shares.iter().enumerate() {
+        if shares[..i].iter().any(|other| other.x == s.x) {
+            return Err(ReconstructError::DuplicateX);
+        }
+    }
+
+    let (basis, rest) = shares.split_at(t + 1);
+    if rest.iter().any(|s| interpolate_at(basis, s.x) != s.value) {
+        return Err(ReconstructError::DegreeTooHigh);
+    }
+    Ok(shamir_reconstruct(basis))
+}
+
+/// value at `x` of the polynomial of degree < points.len() through `points` (distinct x)
+fn interpolate_at<F: PrimeField>(points: &[Share<F>], x: F) -> F {
+    points.iter().enumerate().map(|(i, pi)| {
+        let mut num = F::one();
+        let mut den = F::one();
+        for (j, pj) in points.iter().enumerate() {
+            if i != j {
+                num *= x - pj.x;
+                den *= pi.x - pj.x;
+            }
+        }
+        pi.value * num * den.inverse().unwrap()
+    }).sum()
+}
+
+/// reconstruct from every (t+1)-subset of `shares` and return the value most subsets agree on,
+/// together with the shares that never took part in a subset producing that value
+///
+/// a single corrupted share only spoils the subsets containing it, so with enough extra shares
+/// the honest value wins and the corrupted share is flagged. tries C(k, t+1) subsets for k shares,
+/// so this is meant for small k. returns `None` with fewer than t+1 shares.
+pub fn reconstruct_consensus<F: PrimeField>(shares: &[Share<F>], t: usize) -> Option<(F, Vec<Share<F>>)> {
+    let k = t + 1;
+    if shares.len() < k {
+        return None;
+    }
+
+    // (value, how many subsets produced it, which shares were in those subsets)
+    let mut candidates: Vec<(F, usize, Vec<bool>)> = Vec::new();
+
+    // walk the subsets as sorted index vectors [0, 1, ..., t], [0, 1, ..., t+1], ...
+    let mut subset: Vec<usize> = (0..k).collect();
+    loop {
+        let chosen: Vec<Share<F>> = subset.iter().map(|&i| shares[i]).collect();
+        let value = shamir_reconstruct(&chosen);
+
+        let pos = match candidates.iter().position(|(v, _, _)| *v == value) {
+            Some(pos) => pos,
+            None => {
+                candidates.push((value, 0, vec![false; shares.len()]));
+                candidates.len() - 1
+            }
+        };
+        candidates[pos].1 += 1;
+        for &i in &subset {
+            candidates[pos].2[i] = true;
+        }
+
+        // advance to the next subset: bump the rightmost index that still has room
+        let Some(i) = (0..k).rev().find(|&i| subset[i] < shares.len() - k + i) else { break };
+        subset[i] += 1;
+        for j in i + 1..k {
+            subset[j] = subset[j - 1] + 1;
+        }
+    }
+
+    let (value, _, agreeing) = candidates.into_iter().max_by_key(|(_, count, _)| *count)?;
+    let faulty = shares.iter().zip(agreeing)
+        .filter(|(_, agrees)| !agrees)
+        .map(|(&share, _)| share)
+        .collect();
+    Some((value, faulty))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ark_bn254::Fr;
+    use ark_bn254::{Fr, G1Projective};
     use ark_ff::UniformRand;
 
     #[test]
@@ -110,5 +447,138 @@ mod tests {
         let recovered = shamir_reconstruct(&shares[..3]);
         assert_ne!(secret, recovered); // not guaranteed but likely
     }
-}
 
+    #[test]
+    fn test_beaver_triples_are_consistent() {
+        let triples = generate_beaver_triples::<Fr>(3, 2, 5);
+        assert_eq!(triples.len(), 5);
+
+        for k in 0..3 {
+            let column = |f: fn(&BeaverTriple<Fr>) -> Share<Fr>| -> Vec<Share<Fr>> {
+                triples.iter().map(|party| f(&party[k])).collect()
+            };
+            let a = shamir_reconstruct(&column(|t| t.a)[..3]);
+            let b = shamir_reconstruct(&column(|t| t.b)[..3]);
+            let c = shamir_reconstruct(&column(|t| t.c)[2..]);
+            assert_eq!(a * b, c);
+        }
+    }
+
+    #[test]
+    fn test_consensus_flags_corrupt_share() {
+        let secret = Fr::rand(&mut rand::thread_rng());
+        let mut shares = shamir_share(secret, 2, 6);
+        shares[3].value += Fr::one();
+
+        let (recovered, faulty) = reconstruct_consensus(&shares, 2).unwrap();
+        assert_eq!(recovered, secret);
+        assert_eq!(faulty, vec![shares[3]]);
+    }
+
+    #[test]
+    fn test_consensus_with_honest_shares() {
+        let secret = Fr::rand(&mut rand::thread_rng());
+        let shares = shamir_share(secret, 1, 4);
+
+        assert_eq!(reconstruct_consensus(&shares, 1), Some((secret, vec![])));
+        assert_eq!(reconstruct_consensus(&shares[..1], 1), None);
+    }
+
+    #[test]
+    fn test_sharing_at_arbitrary_x_coordinates() {
+        let secret = Fr::rand(&mut rand::thread_rng());
+        let xs: Vec<Fr> = [5u64, 9, 12, 20, 31].iter().map(|&x| Fr::from(x)).collect();
+        let shares = shamir_share_at(secret, 2, &xs);
+
+        assert_eq!(shares.iter().map(|s| s.x).collect::<Vec<_>>(), xs);
+        // any t+1 subset reconstructs
+        assert_eq!(shamir_reconstruct(&shares[..3]), secret);
+        assert_eq!(shamir_reconstruct(&[shares[1], shares[3], shares[4]]), secret);
+    }
+
+    #[test]
+    fn test_pedersen_commitments_catch_a_bad_share() {
+        let secret = Fr::from(1u64); // low entropy: g^secret would give it away
+        let xs: Vec<Fr> = (1..=4u64).map(Fr::from).collect();
+        let (mut shares, blinding, commitments) = shamir_share_with_commitments(secret, 2, &xs);
+
+        assert_eq!(commitments.len(), 3);
+        assert_ne!(commitments[0], G1Projective::generator() * secret);
+        assert!(shares.iter().zip(&blinding).all(|(s, &b)| verify_share(s, b, &commitments)));
+
+        shares[2].value += Fr::one();
+        assert!(!verify_share(&shares[2], blinding[2], &commitments));
+        // a correct value presented at someone else's point fails too
+        assert!(!verify_share(&Share { x: xs[0], value: shares[1].value }, blinding[1], &commitments));
+        // and so does a correct value with the wrong blinding
+        assert!(!verify_share(&shares[0], blinding[0] + Fr::one(), &commitments));
+    }
+
+    #[test]
+    fn test_batch_round_trip() {
+        let mut rng = rand::thread_rng();
+        let secrets: Vec<Fr> = (0..100).map(|_| Fr::rand(&mut rng)).collect();
+        let shares = shamir_share_batch(&secrets, 2, 5);
+
+        assert_eq!(shares.len(), secrets.len());
+        let subsets: Vec<Vec<Share<Fr>>> = shares.iter().map(|s| s[1..4].to_vec()).collect();
+        assert_eq!(shamir_reconstruct_batch(&subsets), secrets);
+        assert!(shamir_reconstruct_batch::<Fr>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_batch_matches_per_element_path() {
+        let secrets: Vec<Fr> = (0..1000u64).map(Fr::from).collect();
+        let shares = shamir_share_batch(&secrets, 3, 7);
+
+        let batched = shamir_reconstruct_batch(&shares);
+        let single: Vec<Fr> = shares.iter().map(|s| shamir_reconstruct(s)).collect();
+        assert_eq!(batched, single);
+        assert_eq!(batched, secrets);
+    }
+
+    #[test]
+    fn test_precomputed_weights_match_reconstruct() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10 {
+            let xs: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+            let shares = shamir_share_at(Fr::rand(&mut rng), 4, &xs);
+            let ys: Vec<Fr> = shares.iter().map(|s| s.value).collect();
+
+            let weights = LagrangeWeights::new(&xs);
+            assert_eq!(weights.xs(), &xs[..]);
+            assert_eq!(weights.reconstruct_with(&ys), shamir_reconstruct(&shares));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate x values")]
+    fn test_weights_reject_duplicate_x() {
+        LagrangeWeights::new(&[Fr::from(1u64), Fr::from(2u64), Fr::from(1u64)]);
+    }
+
+    #[test]
+    fn test_checked_reconstruction_accepts_consistent_shares() {
+        let secret = Fr::rand(&mut rand::thread_rng());
+        let shares = shamir_share(secret, 2, 6);
+        assert_eq!(shamir_reconstruct_checked(&shares, 2), Ok(secret));
+        assert_eq!(shamir_reconstruct_checked(&shares[..3], 2), Ok(secret));
+        assert_eq!(shamir_reconstruct_checked(&shares[..2], 2), Err(ReconstructError::TooFewShares { have: 2, need: 3 }));
+    }
+
+    #[test]
+    fn test_checked_reconstruction_detects_corrupted_share() {
+        let secret = Fr::rand(&mut rand::thread_rng());
+        let mut shares = shamir_share(secret, 2, 6);
+        shares[4].value += Fr::one();
+        assert_eq!(shamir_reconstruct_checked(&shares, 2), Err(ReconstructError::DegreeTooHigh));
+
+        // a corrupted share among the first t+1 is caught by the others just the same
+        let mut shares = shamir_share(secret, 2, 6);
+        shares[0].value += Fr::one();
+        assert_eq!(shamir_reconstruct_checked(&shares, 2), Err(ReconstructError::DegreeTooHigh));
+
+        let duplicate = [shares[1], shares[2], shares[1]];
+        assert_eq!(shamir_reconstruct_checked(&duplicate, 1), Err(ReconstructError::DuplicateX));
+    }
+}
@@ -0,0 +1,44 @@
+use ark_ff::PrimeField;
+
+use crate::sharing::Share;
+
+// helpers for analysing sharings in tests and experiments; nothing here is used by the protocol.
+
+/// the x-values of the parties whose removal would leave fewer than t+1 distinct points
+///
+/// every party is critical when exactly t+1 distinct x-values are present, and none is when
+/// there are more. with fewer than t+1 reconstruction is already impossible, so dropping a
+/// party changes nothing and none is reported. x-values are listed in order of first appearance.
+pub fn critical_parties<F: PrimeField>(shares: &[Share<F>], t: usize) -> Vec<F> {
+    let mut xs: Vec<F> = Vec::new();
+    for share in shares {
+        if !xs.contains(&share.x) {
+            xs.push(share.x);
+        }
+    }
+
+    if xs.len() == t + 1 { xs } else { Vec::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use crate::sharing::shamir_share;
+
+    #[test]
+    fn test_critical_parties() {
+        let shares = shamir_share(Fr::from(5u64), 2, 5);
+
+        // exactly t+1: losing anyone makes reconstruction impossible
+        let xs: Vec<Fr> = shares[..3].iter().map(|s| s.x).collect();
+        assert_eq!(critical_parties(&shares[..3], 2), xs);
+
+        // t+2: any single party can drop out
+        assert!(critical_parties(&shares[..4], 2).is_empty());
+
+        // a repeated point is still only one party
+        let repeated = [shares[0], shares[1], shares[1], shares[2]];
+        assert_eq!(critical_parties(&repeated, 2), xs);
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, Mutex};
+
+use crate::message::Envelope;
+use crate::sharing::ProtocolField;
+
+/// How a party exchanges messages with the other parties.
+///
+/// Messages from one sender to one recipient are delivered in the order they were sent.
+/// Both halves take `&self` so a party can keep receiving while a send waits on a full peer.
+pub trait Transport<F: ProtocolField>: Send + Sync {
+    /// deliver `msg` to party `to` (which may be this party itself)
+    fn send(&self, to: usize, msg: Envelope<F>) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// the next message addressed to this party, or `None` once every sender is gone
+    fn recv(&self) -> impl Future<Output = Option<Envelope<F>>> + Send;
+}
+
+/// In-process transport over tokio channels (all parties in one runtime)
+pub struct ChannelTransport<F: ProtocolField> {
+    peers: HashMap<usize, mpsc::Sender<Envelope<F>>>, // recipient → Sender<Envelope>
+    inbox: Mutex<mpsc::Receiver<Envelope<F>>>,
+}
+
+impl<F: ProtocolField> ChannelTransport<F> {
+    /// transports for `n` parties, each able to reach every party (including itself)
+    pub fn network(n: usize) -> Vec<ChannelTransport<F>> {
+        let (txs, rxs): (Vec<_>, Vec<_>) = (0..n).map(|_| mpsc::channel::<Envelope<F>>(1024)).unzip();
+
+        rxs.into_iter()
+            .map(|inbox| ChannelTransport {
+                peers: txs.iter().cloned().enumerate().collect(),
+                inbox: Mutex::new(inbox),
+            })
+            .collect()
+    }
+}
+
+impl<F: ProtocolField> Transport<F> for ChannelTransport<F> {
+    async fn send(&self, to: usize, msg: Envelope<F>) -> io::Result<()> {
+        let tx = self.peers.get(&to)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("unknown party {}", to)))?;
+        tx.send(msg).await
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, format!("party {} has hung up", to)))
+    }
+
+    async fn recv(&self) -> Option<Envelope<F>> {
+        self.inbox.lock().await.recv().await
+    }
+}
+
+/// how long `TcpTransport` keeps retrying to reach a peer that isn't listening yet
+const DIAL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Transport over TCP: one connection per ordered pair of parties, each message framed
+/// as a big-endian `u32` length followed by its `Envelope::to_bytes` encoding.
+pub struct TcpTransport<F: ProtocolField> {
+    id: usize,
+    peers: HashMap<usize, Mutex<OwnedWriteHalf>>, // recipient → outgoing connection
+    loopback: mpsc::Sender<Envelope<F>>, // messages to ourselves skip the network
+    inbox: Mutex<mpsc::Receiver<Envelope<F>>>,
+}
+
+impl<F: ProtocolField> TcpTransport<F> {
+    /// listen on `addrs[id]` and dial every other address in `addrs`
+    pub async fn connect(id: usize, addrs: &[SocketAddr]) -> io::Result<TcpTransport<F>> {
+        let listener = TcpListener::bind(addrs[id]).await?;
+        Self::with_listener(id, listener, addrs).await
+    }
+
+    /// like `connect`, but with a listener that is already bound to `addrs[id]`
+    pub async fn with_listener(id: usize, listener: TcpListener, addrs: &[SocketAddr]) -> io::Result<TcpTransport<F>> {
+        let (loopback, inbox) = mpsc::channel(1024);
+
+        // accept one incoming connection per peer, each feeding the shared inbox
+        let incoming = loopback.clone();
+        let expected = addrs.len() - 1;
+        tokio::spawn(async move {
+            for _ in 0..expected {
+                let Ok((stream, _)) = listener.accept().await else { return };
+                tokio::spawn(read_frames(stream, incoming.clone()));
+            }
+        });
+
+        let mut peers = HashMap::new();
+        for (pid, &addr) in addrs.iter().enumerate() {
+            if pid != id {
+                let stream = dial(addr).await?;
+                stream.set_nodelay(true)?;
+                let (_, writer) = stream.into_split();
+                peers.insert(pid, Mutex::new(writer));
+            }
+        }
+
+        Ok(TcpTransport { id, peers, loopback, inbox: Mutex::new(inbox) })
+    }
+}
+
+/// connect to `addr`, retrying while the peer is still starting up
+async fn dial(addr: SocketAddr) -> io::Result<TcpStream> {
+    let deadline = tokio::time::Instant::now() + DIAL_TIMEOUT;
+    loop {
+        match TcpStream::connect(addr).await {
+            Ok(stream) => return Ok(stream),
+            Err(e) if tokio::time::Instant::now() >= deadline => return Err(e),
+            Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+    }
+}
+
+/// decode length-prefixed messages from `stream` until it closes or sends garbage
+///
+/// a frame longer than any message could be (see `Envelope::max_encoded_len`) drops the
+/// connection before anything is allocated for it
+async fn read_frames<F: ProtocolField>(mut stream: TcpStream, inbox: mpsc::Sender<Envelope<F>>) {
+    let max_len = Envelope::<F>::max_encoded_len();
+    let mut len = [0u8; 4];
+    while stream.read_exact(&mut len).await.is_ok() {
+        let len = u32::from_be_bytes(len) as usize;
+        if len > max_len {
+            return;
+        }
+        let mut frame = vec![0u8; len];
+        if stream.read_exact(&mut frame).await.is_err() {
+            return;
+        }
+        let Ok(msg) = Envelope::from_bytes(&frame) else { return };
+        if inbox.send(msg).await.is_err() {
+            return;
+        }
+    }
+}
+
+impl<F: ProtocolField> Transport<F> for TcpTransport<F> {
+    async fn send(&self, to: usize, msg: Envelope<F>) -> io::Result<()> {
+        if to == self.id {
+            return self.loopback.send(msg).await
+                .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "own inbox is closed"));
+        }
+
+        let peer = self.peers.get(&to)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("unknown party {}", to)))?;
+        let bytes = msg.to_bytes();
+        let mut frame = Vec::with_capacity(4 + bytes.len());
+        frame.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&bytes);
+        peer.lock().await.write_all(&frame).await
+    }
+
+    async fn recv(&self) -> Option<Envelope<F>> {
+        self.inbox.lock().await.recv().await
+    }
+}
@@ -0,0 +1,107 @@
+use ark_bn254::Fr;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+
+use bgw::circuit::{Circuit, GateType, ParseError};
+use bgw::party::{Party, PartyConfig};
+use bgw::transport::ChannelTransport;
+
+fn parse(text: &str, owners: &[usize]) -> Result<Circuit<Fr>, ParseError> {
+    Circuit::from_bristol(text.as_bytes(), owners)
+}
+
+#[test]
+fn test_parse_full_adder_fixture() {
+    let file = File::open("tests/fixtures/full_adder.txt").unwrap();
+    let circuit = Circuit::<Fr>::from_bristol(BufReader::new(file), &[0, 1, 2]).unwrap();
+
+    // 3 inputs, 3 XORs (4 gates each), 2 ANDs and 2 outputs
+    assert_eq!(circuit.gates.len(), 3 + 3 * 4 + 2 + 2);
+    assert_eq!(circuit.topological_order().len(), circuit.gates.len());
+
+    assert_eq!(circuit.input_wires_by_owner(0), vec![0]);
+    assert_eq!(circuit.input_wires_by_owner(1), vec![1]);
+    assert_eq!(circuit.input_wires_by_owner(2), vec![2]);
+    assert_eq!(circuit.output_wires().len(), 2);
+    assert_eq!(circuit.gates.iter().filter(|g| matches!(g.gate_type, GateType::Mul)).count(), 5);
+}
+
+#[tokio::test]
+async fn test_parse_nand_fixture() {
+    let file = File::open("tests/fixtures/nand.txt").unwrap();
+    let circuit = Circuit::<Fr>::from_bristol(BufReader::new(file), &[0, 1]).unwrap();
+
+    // INV b becomes 1 + (-1)·b
+    let out = circuit.output_wires()[0];
+    let inv = circuit.gates[out].left.unwrap();
+    assert!(matches!(circuit.gates[inv].gate_type, GateType::AddConst(c) if c == Fr::from(1u64)));
+    let negated = circuit.gates[inv].left.unwrap();
+    assert!(matches!(circuit.gates[negated].gate_type, GateType::ConstMul(c) if c == -Fr::from(1u64)));
+
+    for (a, b) in [(0u64, 0u64), (0, 1), (1, 0), (1, 1)] {
+        let (n, t) = (3, 1);
+        let handles: Vec<_> = ChannelTransport::network(n).into_iter().enumerate().map(|(pid, transport)| {
+            let circuit = circuit.clone();
+            tokio::spawn(async move {
+                let mut party = Party::with_config(pid, PartyConfig::new(n, t), transport);
+                // input wire i is owned by party i; party 2 has no input
+                let inputs: HashMap<usize, Fr> = [a, b].get(pid).map(|&v| (pid, Fr::from(v))).into_iter().collect();
+                party.input_phase(&circuit, &inputs).await.unwrap();
+                party.evaluate_circuit(&circuit).await.unwrap();
+                let output = party.output_phase(&circuit, &[out]).await.unwrap();
+                (party, output)
+            })
+        }).collect();
+
+        let mut results = Vec::new();
+        for h in handles {
+            results.push(h.await.unwrap());
+        }
+        for (_, output) in &results {
+            assert_eq!(output[&out], Fr::from(1 - a * b), "NAND({}, {})", a, b);
+        }
+    }
+}
+
+#[test]
+fn test_parse_arithmetic_gates() {
+    // (a + b) * c, with a 2-wire first input
+    let text = "3 6\n2 2 1\n1 1\n\n2 1 0 1 3 ADD\n2 1 3 2 4 MUL\n1 1 4 5 EQW\n";
+    let circuit = parse(text, &[0, 1]).unwrap();
+
+    assert_eq!(circuit.input_wires_by_owner(0), vec![0, 1]);
+    assert_eq!(circuit.input_wires_by_owner(1), vec![2]);
+    let out = circuit.output_wires()[0];
+    let product = circuit.gates[out].left.unwrap();
+    assert!(matches!(circuit.gates[product].gate_type, GateType::Mul));
+}
+
+#[test]
+fn test_parse_rejects_bad_circuits() {
+    // wire 7 is never defined
+    let undefined = "1 4\n2 1 1\n1 1\n\n2 1 0 7 3 AND\n";
+    assert!(matches!(parse(undefined, &[0, 1]), Err(ParseError::UndefinedWire { line: 5, wire: 7 })));
+
+    let unsupported = "1 4\n2 1 1\n1 1\n\n2 1 0 1 3 NAND\n";
+    assert!(matches!(parse(unsupported, &[0, 1]), Err(ParseError::UnsupportedGate { line: 5, .. })));
+
+    let wrong_count = "2 4\n2 1 1\n1 1\n\n2 1 0 1 3 AND\n";
+    assert!(matches!(parse(wrong_count, &[0, 1]), Err(ParseError::GateCount { expected: 2, got: 1 })));
+
+    let header = "1 4\n2 1\n1 1\n";
+    assert!(matches!(parse(header, &[0, 1]), Err(ParseError::InvalidHeader { line: 2 })));
+
+    // counts big enough to overflow are rejected, not trusted
+    let huge_count = "1 4\n18446744073709551615 1\n1 1\n\n2 1 0 1 3 AND\n";
+    assert!(matches!(parse(huge_count, &[0, 1]), Err(ParseError::InvalidHeader { line: 2 })));
+    let huge_width = "1 4\n2 18446744073709551615 1\n1 1\n\n2 1 0 1 3 AND\n";
+    assert!(matches!(parse(huge_width, &[0, 1]), Err(ParseError::InvalidHeader { line: 2 })));
+    let huge_fan_in = "1 4\n2 1 1\n1 1\n\n18446744073709551615 1 0 1 AND\n";
+    assert!(matches!(parse(huge_fan_in, &[0, 1]), Err(ParseError::InvalidGate { line: 5 })));
+    let no_wires = "1 4\n2 1 1\n1 1\n\n0 1 AND\n";
+    assert!(matches!(parse(no_wires, &[0, 1]), Err(ParseError::InvalidGate { line: 5 })));
+
+    assert!(matches!(parse("1 4\n", &[]), Err(ParseError::UnexpectedEof)));
+    assert!(matches!(parse(undefined, &[0]), Err(ParseError::OwnerCount { expected: 2, got: 1 })));
+}
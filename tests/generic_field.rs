@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use bgw::circuit::{Circuit, GateType};
+use bgw::fields::Goldilocks;
+use bgw::message::Message;
+use bgw::party::{Party, PartyConfig};
+use bgw::sharing::{shamir_reconstruct, shamir_share};
+use bgw::transport::ChannelTransport;
+
+#[test]
+fn test_sharing_and_encoding_over_a_small_field() {
+    let secret = Goldilocks::from(123_456_789u64);
+    let shares = shamir_share(secret, 2, 5);
+    assert_eq!(shamir_reconstruct(&shares[1..4]), secret);
+
+    // 8-byte field elements instead of BN254's 32
+    let msg = Message::OutputShare(3, shares[0]);
+    let bytes = msg.to_bytes();
+    assert_eq!(bytes.len(), 1 + 8 + 2 * 8);
+    assert_eq!(Message::from_bytes(&bytes), Ok(msg));
+}
+
+#[tokio::test]
+async fn test_parties_agree_over_a_small_field() {
+    let (n, t) = (3, 1);
+
+    // (a + b) * c with a, b, c owned by parties 0, 1, 2
+    let mut circuit = Circuit::<Goldilocks>::new();
+    let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+    let b = circuit.add_gate(GateType::Input, None, None, Some(1));
+    let c = circuit.add_gate(GateType::Input, None, None, Some(2));
+    let sum = circuit.add_gate(GateType::Add, Some(a), Some(b), None);
+    let product = circuit.add_gate(GateType::Mul, Some(sum), Some(c), None);
+    let out = circuit.add_gate(GateType::Output, Some(product), None, None);
+
+    let handles: Vec<_> = ChannelTransport::network(n).into_iter().enumerate().map(|(pid, transport)| {
+        let circuit = circuit.clone();
+        tokio::spawn(async move {
+            let mut party = Party::with_config(pid, PartyConfig::new(n, t), transport);
+            let inputs = HashMap::from([(pid, Goldilocks::from(pid as u64 + 2))]);
+            party.input_phase(&circuit, &inputs).await.unwrap();
+            party.evaluate_circuit(&circuit).await.unwrap();
+            let output = party.output_phase(&circuit, &[out]).await.unwrap();
+            (party, output) // keep the inbox open until everyone is done
+        })
+    }).collect();
+
+    let mut results = Vec::new();
+    for h in handles {
+        results.push(h.await.unwrap());
+    }
+
+    // (2 + 3) * 4
+    for (_, output) in &results {
+        assert_eq!(output[&out], Goldilocks::from(20u64));
+    }
+}
@@ -0,0 +1,83 @@
+use ark_bn254::Fr;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use bgw::circuit::{Circuit, GateType};
+use bgw::message::Envelope;
+use bgw::party::{Party, PartyConfig};
+use bgw::transport::TcpTransport;
+
+#[tokio::test]
+async fn test_parties_agree_over_loopback_tcp() {
+    let (n, t) = (3, 1);
+    let run_id = 1;
+
+    // (a + b) * c with a, b, c owned by parties 0, 1, 2
+    let mut circuit = Circuit::new();
+    let a = circuit.add_gate(GateType::Input, None, None, Some(0));
+    let b = circuit.add_gate(GateType::Input, None, None, Some(1));
+    let c = circuit.add_gate(GateType::Input, None, None, Some(2));
+    let sum = circuit.add_gate(GateType::Add, Some(a), Some(b), None);
+    let product = circuit.add_gate(GateType::Mul, Some(sum), Some(c), None);
+    let out = circuit.add_gate(GateType::Output, Some(product), None, None);
+
+    // bind up front so every party knows every address before anyone dials
+    let mut listeners = Vec::new();
+    for _ in 0..n {
+        listeners.push(TcpListener::bind("127.0.0.1:0").await.unwrap());
+    }
+    let addrs: Vec<_> = listeners.iter().map(|l| l.local_addr().unwrap()).collect();
+
+    let handles: Vec<_> = listeners.into_iter().enumerate().map(|(pid, listener)| {
+        let circuit = circuit.clone();
+        let addrs = addrs.clone();
+        tokio::spawn(async move {
+            let transport = TcpTransport::with_listener(pid, listener, &addrs).await.unwrap();
+            let config = PartyConfig { run_id, ..PartyConfig::new(n, t) };
+            let mut party = Party::with_config(pid, config, transport);
+
+            let inputs = HashMap::from([(pid, Fr::from(pid as u64 + 2))]);
+            party.input_phase(&circuit, &inputs).await.unwrap();
+            party.evaluate_circuit(&circuit).await.unwrap();
+            let output = party.output_phase(&circuit, &[out]).await.unwrap();
+            (party, output) // keep connections open until everyone is done
+        })
+    }).collect();
+
+    let mut results = Vec::new();
+    for h in handles {
+        results.push(h.await.unwrap());
+    }
+
+    // (2 + 3) * 4
+    for (_, output) in &results {
+        assert_eq!(output[&out], Fr::from(20u64));
+    }
+}
+
+#[tokio::test]
+async fn test_oversized_frame_drops_the_connection() {
+    let honest = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let rogue = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addrs = vec![honest.local_addr().unwrap(), rogue.local_addr().unwrap()];
+
+    let transport = tokio::spawn({
+        let addrs = addrs.clone();
+        async move { TcpTransport::<Fr>::with_listener(0, honest, &addrs).await.unwrap() }
+    });
+    let (_dialed_by_honest, _) = rogue.accept().await.unwrap();
+    let _transport = transport.await.unwrap();
+
+    // announce a frame one byte past the largest message, then send nothing more: a reader
+    // that trusted the header would sit waiting for the body instead of hanging up
+    let mut stream = TcpStream::connect(addrs[0]).await.unwrap();
+    let len = Envelope::<Fr>::max_encoded_len() as u32 + 1;
+    stream.write_all(&len.to_be_bytes()).await.unwrap();
+
+    let mut buf = [0u8; 1];
+    let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf)).await
+        .expect("the connection should be dropped");
+    assert!(matches!(read, Ok(0) | Err(_)));
+}